@@ -6,4 +6,5 @@ fn main() {
         .file("src/libosmium.cpp")
         .compile("osmium");
     println!("cargo:rustc-link-lib=z");
+    println!("cargo:rustc-link-lib=expat");
 }
@@ -1,9 +1,9 @@
 use std::env;
 
-use libosmium::{Handler, ItemBuffer, ItemRef, Node};
+use libosmium::{Handler, Node, TypedBuffer};
 
 struct NodeBuffer {
-    buffer: ItemBuffer,
+    buffer: TypedBuffer<Node>,
 }
 
 impl Handler for NodeBuffer {
@@ -19,20 +19,14 @@ fn main() -> Result<(), String> {
         .ok_or("Missing file".to_string())?;
 
     let mut handler = NodeBuffer {
-        buffer: ItemBuffer::new(),
+        buffer: TypedBuffer::new(),
     };
 
-    handler
-        .apply(&file)
-        .map_err(|cstr| cstr.to_string_lossy().to_string())?;
+    handler.apply(&file).map_err(|error| error.to_string())?;
 
     for node in handler.buffer.iter() {
-        if let Some(ItemRef::Node(node)) = node.cast() {
-            if !node.tags().is_empty() {
-                println!("{:?}", node.tags())
-            }
-        } else {
-            unreachable!("The buffer was only populated with nodes");
+        if !node.tags().is_empty() {
+            println!("{:?}", node.tags())
         }
     }
 
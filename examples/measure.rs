@@ -68,9 +68,7 @@ fn main() -> Result<(), String> {
         .ok_or("Missing file".to_string())?;
 
     let mut handler = BoundingBox::default();
-    handler
-        .apply(&file)
-        .map_err(|cstr| cstr.to_string_lossy().to_string())?;
+    handler.apply(&file).map_err(|error| error.to_string())?;
 
     println!("{handler:?}");
     println!("{:?}", Center::from(handler));
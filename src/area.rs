@@ -1,7 +1,11 @@
 use std::marker::PhantomData;
 use std::os::raw::c_char;
 
+use crate::geometry;
+use crate::location::{BBox, Location};
 use crate::node_ref_list::{InnerRing, OuterRing};
+#[cfg(feature = "serde")]
+use crate::object::OSMObject;
 use crate::object::ObjectId;
 
 /// An area, as the name suggests, is some mapped area with tags.
@@ -16,9 +20,10 @@ use crate::object::ObjectId;
 /// ## Multipolygon
 /// A multipolygon consists of multiple polygons named "outer rings" which contain "inner rings".
 /// These are polygons as well, which are "cut out" from their outer ring.
-/// Use [`outer_rings`](Area::outer_rings) and [`inner_rings`](Area::inner_rings) to access the area's rings.
+/// Use [`outer_rings`](Area::outer_rings) and [`inner_rings`](Area::inner_rings) or [`polygons`](Area::polygons) to access the area's rings.
 ///
-/// Each ring i.e. polygon is just a [list of points](crate::node_ref_list::NodeRefList).
+/// Each ring i.e. polygon is just a [list of points](crate::node_ref_list::NodeRefList)
+/// with some additional methods like [`contains`](OuterRing::contains).
 ///
 /// While the general shape of an area is a multipolygon, most areas are just a single outer ring without any inner ones.
 /// For example most residential buildings are just a single polygon.
@@ -62,13 +67,127 @@ impl Area {
     }
 
     /// Return an iterator over all inner rings in the given outer ring.
-    // TODO consider making this a method on OuterRing?
+    ///
+    /// The outer ring has to be one of this area's.
+    /// An outer ring doesn't know where its area ends, so the area is needed to find the end of its inner rings.
     pub fn inner_rings<'a>(
         &'a self,
         outer: &'a OuterRing,
     ) -> impl Iterator<Item = &'a InnerRing> + 'a {
         unsafe { area_inner_rings(self, outer) }
     }
+
+    /// Calculate the bounding box of all outer rings.
+    ///
+    /// The box is undefined for areas without rings.
+    pub fn envelope(&self) -> BBox {
+        let mut envelope = BBox::new();
+        for outer in self.outer_rings() {
+            let ring = outer.envelope();
+            if ring.is_defined() {
+                envelope.extend(ring.bottom_left).extend(ring.top_right);
+            }
+        }
+        envelope
+    }
+
+    /// Check whether a location is inside the area, i.e. inside an outer ring but none of its inner rings.
+    ///
+    /// Locations exactly on a ring may be considered inside or outside.
+    ///
+    /// To check many locations against many areas, use an [AreaLookup](crate::area_lookup::AreaLookup).
+    pub fn contains(&self, location: Location) -> bool {
+        self.polygons().any(|(outer, inner)| {
+            geometry::polygon_contains(
+                geometry::locations(outer),
+                inner.map(|inner| geometry::locations(inner)),
+                location,
+            )
+        })
+    }
+
+    /// Check whether the area and a box share at least one point.
+    pub fn intersects_bbox(&self, bbox: &BBox) -> bool {
+        self.envelope().intersects(bbox)
+            && (self.boundary_intersects_bbox(bbox) || self.contains(bbox.bottom_left))
+    }
+
+    /// Check whether a box is entirely inside the area.
+    ///
+    /// Boxes touching one of the area's rings are considered not to be inside.
+    pub fn contains_bbox(&self, bbox: &BBox) -> bool {
+        bbox.is_defined() && !self.boundary_intersects_bbox(bbox) && self.contains(bbox.bottom_left)
+    }
+
+    /// Check whether the area is entirely inside a box.
+    pub fn is_within_bbox(&self, bbox: &BBox) -> bool {
+        bbox.contains_bbox(&self.envelope())
+    }
+
+    /// Check whether any of the area's rings touches a box
+    fn boundary_intersects_bbox(&self, bbox: &BBox) -> bool {
+        self.polygons().any(|(outer, inner)| {
+            geometry::polygon_rings_intersect_bbox(
+                geometry::locations(outer),
+                inner.map(|inner| geometry::locations(inner)),
+                bbox,
+            )
+        })
+    }
+
+    /// Return an iterator over all polygons, i.e. all outer rings together with their inner rings.
+    ///
+    /// ```no_run
+    /// # fn print(area: &libosmium::Area) {
+    /// for (outer, inner) in area.polygons() {
+    ///     let holes: f64 = inner.map(|ring| ring.signed_area().abs()).sum();
+    ///     println!("Polygon of {} square degrees", outer.signed_area().abs() - holes);
+    /// }
+    /// # }
+    /// ```
+    pub fn polygons(
+        &self,
+    ) -> impl Iterator<Item = (&OuterRing, impl Iterator<Item = &InnerRing> + '_)> + '_ {
+        self.outer_rings()
+            .map(|outer| (outer, self.inner_rings(outer)))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Area {
+    /// Serialize the area with its rings as a list of outer rings which each contain their inner rings
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{SerializeSeq, SerializeStruct};
+
+        /// Helper to serialize an outer ring together with its inner rings
+        struct Rings<'a>(&'a Area, &'a OuterRing);
+        impl serde::Serialize for Rings<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let inner: Vec<&InnerRing> = self.0.inner_rings(self.1).collect();
+                let mut state = serializer.serialize_struct("Rings", 2)?;
+                state.serialize_field("outer", self.1)?;
+                state.serialize_field("inner", &inner)?;
+                state.end()
+            }
+        }
+
+        /// Helper to serialize the list of outer rings
+        struct OuterRings<'a>(&'a Area);
+        impl serde::Serialize for OuterRings<'_> {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut seq = serializer.serialize_seq(Some(self.0.num_rings().0))?;
+                for outer in self.0.outer_rings() {
+                    seq.serialize_element(&Rings(self.0, outer))?;
+                }
+                seq.end()
+            }
+        }
+
+        let mut state = serializer.serialize_struct("Area", OSMObject::SERIALIZED_FIELDS + 1)?;
+        self.serialize_fields(&mut state)?;
+        state.serialize_field("rings", &OuterRings(self))?;
+        state.end()
+    }
 }
 
 /// Iterator with same memory layout as c++'s `osmium::memory::ItemIterator`
@@ -0,0 +1,267 @@
+//! defines the [AreaAssembler] which builds [Areas](crate::Area) from ways and relations held in memory.
+//!
+//! [`Handler::apply_with_areas`] reads a file twice to assemble areas.
+//! The assembler instead can be fed ways and relations directly,
+//! for example from an [ItemBuffer] or a handler of your own.
+//!
+//! All ways need their nodes' locations to be set, for example by reading them
+//! using [`apply_with_ways`](Handler::apply_with_ways).
+
+use std::ffi::{c_void, CString};
+use std::mem;
+use std::os::raw::c_char;
+
+use crate::buffer::{extend_buffer, BufferCallback, ItemBuffer};
+use crate::handler::{catch_error, AreaAssemblerConfig, Handler};
+use crate::item::ItemType;
+use crate::object::{OSMObject, ObjectId};
+use crate::relation::{Relation, RelationMember};
+use crate::relations_manager::{Relations, RelationsManager};
+use crate::way::Way;
+
+/// Assembles [Areas](crate::Area) from closed ways and multipolygon relations
+///
+/// This wraps libosmium's `osmium::area::Assembler` for single areas
+/// ([`assemble_way`](AreaAssembler::assemble_way) and [`assemble_relation`](AreaAssembler::assemble_relation))
+/// and mimics `osmium::area::MultipolygonManager` to be fed many objects
+/// ([`add_relation`](AreaAssembler::add_relation) and [`add_way`](AreaAssembler::add_way)).
+///
+/// ```no_run
+/// use libosmium::area_assembler::AreaAssembler;
+/// use libosmium::handler::AreaAssemblerConfig;
+/// use libosmium::ItemBuffer;
+///
+/// # let buffer = ItemBuffer::new();
+/// // A buffer of relations and ways with locations
+/// let mut assembler = AreaAssembler::new(AreaAssemblerConfig::default());
+/// assembler.add_buffer(&buffer).unwrap();
+/// for area in assembler.take_areas().areas() {
+///     println!("Area {} has {} outer rings", area.id(), area.outer_rings().count());
+/// }
+/// for id in assembler.finish() {
+///     println!("Relation {id} is missing some of its ways");
+/// }
+/// ```
+pub struct AreaAssembler {
+    relations: Relations<Multipolygons>,
+}
+
+impl AreaAssembler {
+    /// Create an assembler using a config.
+    pub fn new(config: AreaAssemblerConfig) -> Self {
+        Self {
+            relations: Relations::new(Multipolygons {
+                config,
+                areas: ItemBuffer::new(),
+                incomplete: Vec::new(),
+                error: None,
+            }),
+        }
+    }
+
+    /// Get the assembler's config
+    pub fn config(&self) -> &AreaAssemblerConfig {
+        &self.relations.manager().config
+    }
+
+    /// Assemble an area from a single closed way and append it to `areas`.
+    ///
+    /// Returns whether the assembly succeeded.
+    /// If [`create_empty_areas`](AreaAssemblerConfig::create_empty_areas) is set,
+    /// an area without rings is appended on failure.
+    pub fn assemble_way(&self, way: &Way, areas: &mut ItemBuffer) -> Result<bool, CString> {
+        assemble_way(self.config(), way, areas)
+    }
+
+    /// Assemble an area from a multipolygon relation and its member ways and append it to `areas`.
+    ///
+    /// `ways` has to contain the relation's way members in their order.
+    /// Members which aren't ways are ignored, as are way members with id 0.
+    /// An error is returned, if the number of ways doesn't match the relation's way members.
+    /// Returns whether the assembly succeeded.
+    /// If [`create_empty_areas`](AreaAssemblerConfig::create_empty_areas) is set,
+    /// an area without rings is appended on failure.
+    pub fn assemble_relation(
+        &self,
+        relation: &Relation,
+        ways: &[&Way],
+        areas: &mut ItemBuffer,
+    ) -> Result<bool, CString> {
+        assemble_relation(self.config(), relation, ways, areas)
+    }
+
+    /// Add a relation which might be a multipolygon.
+    ///
+    /// Multipolygon and boundary relations are stored until all their member ways have been [added](AreaAssembler::add_way).
+    /// Therefore, relations have to be added before their ways.
+    pub fn add_relation(&mut self, relation: &Relation) {
+        self.relations.first_pass().relation(relation);
+    }
+
+    /// Add a way.
+    ///
+    /// If the way is the last missing member of some relations, their areas are assembled.
+    /// If the way is closed, tagged, not tagged `area=no` and not a member of any relation,
+    /// its own area is assembled.
+    pub fn add_way(&mut self, way: &Way) -> Result<(), CString> {
+        if self.relations.is_waiting_for(ItemType::Way, way.id()) {
+            self.relations.second_pass().osm_object(way);
+        } else if is_area(way) {
+            let multipolygons = self.relations.manager_mut();
+            assemble_way(&multipolygons.config, way, &mut multipolygons.areas)?;
+        }
+
+        match self.relations.manager_mut().error.take() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Add all relations and ways from a buffer.
+    ///
+    /// The relations are added first, so the buffer's order doesn't matter.
+    pub fn add_buffer(&mut self, buffer: &ItemBuffer) -> Result<(), CString> {
+        for relation in buffer.relations() {
+            self.add_relation(relation);
+        }
+        for way in buffer.ways() {
+            self.add_way(way)?;
+        }
+        Ok(())
+    }
+
+    /// Take the areas assembled by [`add_way`](AreaAssembler::add_way) so far.
+    pub fn take_areas(&mut self) -> ItemBuffer {
+        mem::take(&mut self.relations.manager_mut().areas)
+    }
+
+    /// Drop the relations still waiting for some of their ways and return their ids.
+    ///
+    /// Call this after all ways have been added. Relations are kept only until their areas are assembled,
+    /// so this frees everything the assembler still holds, except the areas to [take](AreaAssembler::take_areas).
+    pub fn finish(&mut self) -> Vec<ObjectId> {
+        self.relations.second_pass().flush();
+        mem::take(&mut self.relations.manager_mut().incomplete)
+    }
+}
+
+/// Should a way not in any relation be assembled into an area?
+///
+/// Like `osmium::area::MultipolygonManager` this skips untagged ways and ways tagged `area=no`,
+/// compares the ends' locations and rejects ways with invalid ones.
+fn is_area(way: &Way) -> bool {
+    if way.tags().is_empty() || way.tags().get("area") == Some("no") {
+        return false;
+    }
+    let nodes = way.nodes();
+    if nodes.len() <= 3 {
+        return false;
+    }
+    match (
+        nodes[0].get_location(),
+        nodes[nodes.len() - 1].get_location(),
+    ) {
+        (Some(first), Some(last)) => first == last,
+        _ => false,
+    }
+}
+
+/// [RelationsManager] for multipolygon relations and their ways
+struct Multipolygons {
+    config: AreaAssemblerConfig,
+    areas: ItemBuffer,
+
+    /// Ids of the relations which were missing ways when the assembler was finished
+    incomplete: Vec<ObjectId>,
+
+    /// First error which occurred while completing a relation
+    error: Option<CString>,
+}
+
+impl RelationsManager for Multipolygons {
+    fn new_relation(&self, relation: &Relation) -> bool {
+        matches!(
+            relation.tags().get("type"),
+            Some("multipolygon" | "boundary")
+        ) && !relation.members().is_empty()
+    }
+
+    fn new_member(&self, _relation: &Relation, member: &RelationMember, _n: usize) -> bool {
+        member.member_type() == ItemType::Way
+    }
+
+    fn complete_relation(&mut self, relation: &Relation, members: &[Option<&OSMObject>]) {
+        let ways: Vec<&Way> = members
+            .iter()
+            .flatten()
+            .filter_map(|member| member.as_ref().downcast::<Way>())
+            .collect();
+        if let Err(error) = assemble_relation(&self.config, relation, &ways, &mut self.areas) {
+            self.error.get_or_insert(error);
+        }
+    }
+
+    fn incomplete_relation(&mut self, relation: &Relation, _members: &[Option<&OSMObject>]) {
+        self.incomplete.push(relation.id());
+    }
+}
+
+/// Implementation of [`AreaAssembler::assemble_way`]
+fn assemble_way(
+    config: &AreaAssemblerConfig,
+    way: &Way,
+    areas: &mut ItemBuffer,
+) -> Result<bool, CString> {
+    let mut success = false;
+    catch_error(|error| unsafe {
+        success = area_assembler_way(
+            config,
+            way,
+            areas as *mut ItemBuffer as *mut c_void,
+            extend_buffer,
+            error,
+        );
+    })?;
+    Ok(success)
+}
+
+/// Implementation of [`AreaAssembler::assemble_relation`]
+fn assemble_relation(
+    config: &AreaAssemblerConfig,
+    relation: &Relation,
+    ways: &[&Way],
+    areas: &mut ItemBuffer,
+) -> Result<bool, CString> {
+    let mut success = false;
+    catch_error(|error| unsafe {
+        success = area_assembler_relation(
+            config,
+            relation,
+            ways.as_ptr(),
+            ways.len(),
+            areas as *mut ItemBuffer as *mut c_void,
+            extend_buffer,
+            error,
+        );
+    })?;
+    Ok(success)
+}
+
+extern "C" {
+    fn area_assembler_way(
+        config: &AreaAssemblerConfig,
+        way: &Way,
+        context: *mut c_void,
+        callback: BufferCallback,
+        error_buffer: *mut c_char,
+    ) -> bool;
+    fn area_assembler_relation(
+        config: &AreaAssemblerConfig,
+        relation: &Relation,
+        ways: *const &Way,
+        num_ways: usize,
+        context: *mut c_void,
+        callback: BufferCallback,
+        error_buffer: *mut c_char,
+    ) -> bool;
+}
@@ -0,0 +1,162 @@
+//! defines the [AreaLookup] to find the areas containing a location.
+//!
+//! A typical use is assigning nodes to the administrative boundaries they are in:
+//!
+//! ```no_run
+//! use libosmium::area_lookup::AreaLookup;
+//! use libosmium::handler::{from_fns, AreaAssemblerConfig, Handler};
+//!
+//! // Collect all boundaries
+//! let mut boundaries = AreaLookup::new();
+//! from_fns()
+//!     .area(|area| {
+//!         if area.tags().get("boundary") == Some("administrative") {
+//!             boundaries.push(area);
+//!         }
+//!     })
+//!     .apply_with_areas("country.osm.pbf", AreaAssemblerConfig::default())?;
+//!
+//! // Look up every shop
+//! from_fns()
+//!     .node(|node| {
+//!         if node.tags().get("shop").is_some() {
+//!             for boundary in boundaries.find(node.location()) {
+//!                 println!("Shop {} is in {:?}", node.id(), boundary.tags().get("name"));
+//!             }
+//!         }
+//!     })
+//!     .apply("country.osm.pbf")?;
+//! # Ok::<(), libosmium::io::Error>(())
+//! ```
+
+use std::collections::HashMap;
+
+use crate::area::Area;
+use crate::buffer::ItemBuffer;
+use crate::handler::Handler;
+use crate::location::{BBox, Location, PRECISION};
+
+/// Size of the grid's cells in `1/PRECISION` degrees
+const CELL_SIZE: i32 = PRECISION;
+
+/// Collection of areas to look up by location or box
+///
+/// The areas are copied and sorted into a grid of one degree cells by their envelopes.
+/// Lookups then only test the areas whose envelopes overlap the location's or box's cells.
+///
+/// As a [Handler] it collects every area it is applied to.
+#[derive(Debug, Default)]
+pub struct AreaLookup {
+    /// The collected areas
+    areas: ItemBuffer,
+
+    /// Every area's offset in `areas` and its envelope
+    entries: Vec<(usize, BBox)>,
+
+    /// Indices into `entries` of the areas overlapping each cell
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl AreaLookup {
+    /// Create an empty lookup.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an area.
+    ///
+    /// Areas without rings are stored, but never found.
+    pub fn push(&mut self, area: &Area) {
+        let index = self.entries.len();
+        let envelope = area.envelope();
+        self.entries.push((self.areas.as_bytes().len(), envelope));
+        self.areas.push(area);
+
+        if envelope.is_defined() {
+            let (min, max) = cell_range(&envelope);
+            for x in min.0..=max.0 {
+                for y in min.1..=max.1 {
+                    self.cells.entry((x, y)).or_default().push(index);
+                }
+            }
+        }
+    }
+
+    /// Get the number of areas
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Is the lookup empty?
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Return an iterator over all areas in the order they have been added.
+    pub fn areas(&self) -> impl Iterator<Item = &Area> {
+        self.areas.areas()
+    }
+
+    /// Return an iterator over the areas containing a location.
+    pub fn find(&self, location: Location) -> impl Iterator<Item = &Area> + '_ {
+        let candidates = if location.is_defined() {
+            self.cells.get(&cell(location)).map(Vec::as_slice)
+        } else {
+            None
+        };
+        candidates
+            .unwrap_or_default()
+            .iter()
+            .filter(move |index| self.entries[**index].1.contains(location))
+            .map(|index| self.get(*index))
+            .filter(move |area| area.contains(location))
+    }
+
+    /// Return an iterator over the areas sharing at least one point with a box.
+    ///
+    /// The areas are returned in the order they have been added.
+    pub fn intersecting(&self, bbox: &BBox) -> impl Iterator<Item = &Area> + '_ {
+        let mut candidates: Vec<usize> = Vec::new();
+        if bbox.is_defined() {
+            let (min, max) = cell_range(bbox);
+            for x in min.0..=max.0 {
+                for y in min.1..=max.1 {
+                    candidates.extend(self.cells.get(&(x, y)).into_iter().flatten());
+                }
+            }
+            candidates.sort_unstable();
+            candidates.dedup();
+        }
+        let bbox = *bbox;
+        candidates
+            .into_iter()
+            .filter(move |index| self.entries[*index].1.intersects(&bbox))
+            .map(|index| self.get(index))
+            .filter(move |area| area.intersects_bbox(&bbox))
+    }
+
+    /// Get the area of an entry
+    fn get(&self, index: usize) -> &Area {
+        let item = unsafe { self.areas.get_unchecked(self.entries[index].0) };
+        item.downcast().expect("Only areas are stored")
+    }
+}
+
+impl Handler for AreaLookup {
+    fn area(&mut self, area: &Area) {
+        self.push(area);
+    }
+}
+
+/// Get the grid cell containing a location
+fn cell(location: Location) -> (i32, i32) {
+    (
+        location.raw_x.div_euclid(CELL_SIZE),
+        location.raw_y.div_euclid(CELL_SIZE),
+    )
+}
+
+/// Get the grid cells at the corners of a box
+fn cell_range(bbox: &BBox) -> ((i32, i32), (i32, i32)) {
+    (cell(bbox.bottom_left), cell(bbox.top_right))
+}
@@ -0,0 +1,514 @@
+//! defines the [Boundary], an owned multipolygon to select objects by location.
+//!
+//! Unlike an [Area] a boundary isn't an osm item stored in a buffer
+//! but a plain list of polygons which can be created from a [BBox] or an [Area]
+//! or read from a file:
+//!
+//! - [`Boundary::from_poly`] parses the [Osmosis polygon filter format](https://wiki.openstreetmap.org/wiki/Osmosis/Polygon_Filter_File_Format)
+//!   and [`Boundary::to_poly`] writes it.
+//! - With the `serde` feature a boundary can be deserialized from a GeoJSON `Polygon` or `MultiPolygon`
+//!   (or a `Feature`, `FeatureCollection` or `GeometryCollection` of them)
+//!   and serializes as a GeoJSON `MultiPolygon`.
+//!
+//! ```no_run
+//! use libosmium::boundary::Boundary;
+//! use libosmium::extract::Extract;
+//!
+//! let poly = std::fs::read_to_string("berlin.poly").unwrap();
+//! let extract = Extract::new(Boundary::from_poly(&poly).unwrap());
+//! ```
+
+use std::error::Error;
+use std::fmt::{Display, Formatter, Write};
+
+use crate::area::Area;
+use crate::geometry;
+use crate::location::{BBox, Location};
+
+/// A multipolygon given by a list of [Polygons](Polygon)
+///
+/// A location is inside the boundary, if it is inside any of its polygons.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Boundary {
+    polygons: Vec<Polygon>,
+    envelope: BBox,
+}
+
+/// A polygon given by an outer ring and the inner rings cut out from it
+///
+/// The rings are lists of locations which are treated as closed,
+/// i.e. the last location doesn't need to repeat the first one.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Polygon {
+    /// The ring enclosing the polygon
+    pub outer: Vec<Location>,
+
+    /// The rings enclosing the polygon's holes
+    pub inner: Vec<Vec<Location>>,
+}
+
+impl Polygon {
+    /// Check whether a location is inside the outer ring, but none of the inner rings.
+    ///
+    /// Locations exactly on a ring may be considered inside or outside.
+    pub fn contains(&self, location: Location) -> bool {
+        geometry::polygon_contains(
+            self.outer.iter().copied(),
+            self.inner.iter().map(|inner| inner.iter().copied()),
+            location,
+        )
+    }
+}
+
+impl Boundary {
+    /// Create a boundary from its polygons.
+    pub fn new(polygons: Vec<Polygon>) -> Self {
+        let mut envelope = BBox::new();
+        for location in polygons.iter().flat_map(|polygon| &polygon.outer) {
+            envelope.extend(*location);
+        }
+        Self { polygons, envelope }
+    }
+
+    /// Get the polygons
+    pub fn polygons(&self) -> &[Polygon] {
+        &self.polygons
+    }
+
+    /// Unwrap the polygons
+    pub fn into_polygons(self) -> Vec<Polygon> {
+        self.polygons
+    }
+
+    /// Get the bounding box of all outer rings
+    pub fn envelope(&self) -> BBox {
+        self.envelope
+    }
+
+    /// Check whether a location is inside any of the polygons.
+    pub fn contains(&self, location: Location) -> bool {
+        self.envelope.contains(location)
+            && self
+                .polygons
+                .iter()
+                .any(|polygon| polygon.contains(location))
+    }
+
+    /// Parse a boundary from the Osmosis polygon filter format (`.poly` files).
+    ///
+    /// The file consists of a name, followed by rings of longitude and latitude pairs, each ended by `END`.
+    /// Rings whose name starts with `!` are holes in the preceding ring.
+    pub fn from_poly(text: &str) -> Result<Self, InvalidPoly> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(index, line)| (index + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty());
+        let error = |line, reason| InvalidPoly { line, reason };
+
+        // The file's name is irrelevant
+        lines.next().ok_or(error(1, "missing name"))?;
+
+        let mut polygons: Vec<Polygon> = Vec::new();
+        loop {
+            let (number, header) = lines.next().ok_or(error(0, "missing END of file"))?;
+            if header == "END" {
+                break;
+            }
+
+            let mut ring = Vec::new();
+            loop {
+                let (number, line) = lines.next().ok_or(error(0, "missing END of ring"))?;
+                if line == "END" {
+                    break;
+                }
+                let mut coordinates = line.split_whitespace().map(str::parse::<f64>);
+                match (coordinates.next(), coordinates.next(), coordinates.next()) {
+                    (Some(Ok(lon)), Some(Ok(lat)), None) => ring.push(Location::new(lon, lat)),
+                    _ => return Err(error(number, "expected longitude and latitude")),
+                }
+            }
+
+            if header.starts_with('!') {
+                polygons
+                    .last_mut()
+                    .ok_or(error(number, "hole before any outer ring"))?
+                    .inner
+                    .push(ring);
+            } else {
+                polygons.push(Polygon {
+                    outer: ring,
+                    inner: Vec::new(),
+                });
+            }
+        }
+        Ok(Self::new(polygons))
+    }
+
+    /// Write the boundary in the Osmosis polygon filter format (`.poly` files).
+    ///
+    /// Use `Boundary::from(area).to_poly(name)` to write an assembled [Area].
+    pub fn to_poly(&self, name: &str) -> String {
+        let mut poly = format!("{name}\n");
+        let mut number = 0;
+        let mut write_ring = |poly: &mut String, hole: bool, ring: &[Location]| {
+            number += 1;
+            let prefix = if hole { "!" } else { "" };
+            let _ = writeln!(poly, "{prefix}{number}");
+            for location in closed(ring) {
+                let _ = writeln!(poly, "\t{:.7}\t{:.7}", location.lon(), location.lat());
+            }
+            poly.push_str("END\n");
+        };
+        for polygon in &self.polygons {
+            write_ring(&mut poly, false, &polygon.outer);
+            for inner in &polygon.inner {
+                write_ring(&mut poly, true, inner);
+            }
+        }
+        poly.push_str("END\n");
+        poly
+    }
+}
+
+/// Iterate over a ring's locations repeating the first one at the end, unless it already does
+fn closed(ring: &[Location]) -> impl Iterator<Item = &Location> {
+    let repeat = match (ring.first(), ring.last()) {
+        (Some(first), Some(last)) if first != last => Some(first),
+        _ => None,
+    };
+    ring.iter().chain(repeat)
+}
+
+/// Error returned by [`Boundary::from_poly`] when the text isn't a well formed polygon file
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InvalidPoly {
+    line: usize,
+    reason: &'static str,
+}
+
+impl InvalidPoly {
+    /// Get the line number the malformed data was found at
+    ///
+    /// This is zero, if the text ended unexpectedly.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl Display for InvalidPoly {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.line == 0 {
+            write!(f, "invalid polygon file at its end: {}", self.reason)
+        } else {
+            write!(
+                f,
+                "invalid polygon file in line {}: {}",
+                self.line, self.reason
+            )
+        }
+    }
+}
+
+impl Error for InvalidPoly {}
+
+impl From<BBox> for Boundary {
+    /// Create a boundary with a single rectangular polygon
+    fn from(bbox: BBox) -> Self {
+        if !bbox.is_defined() {
+            return Self::default();
+        }
+        let BBox {
+            bottom_left,
+            top_right,
+        } = bbox;
+        let outer = vec![
+            bottom_left,
+            Location {
+                raw_x: top_right.raw_x,
+                raw_y: bottom_left.raw_y,
+            },
+            top_right,
+            Location {
+                raw_x: bottom_left.raw_x,
+                raw_y: top_right.raw_y,
+            },
+        ];
+        Self::new(vec![Polygon {
+            outer,
+            inner: Vec::new(),
+        }])
+    }
+}
+
+impl From<&Area> for Boundary {
+    /// Copy an area's rings
+    ///
+    /// Nodes with invalid locations are skipped.
+    fn from(area: &Area) -> Self {
+        Self::new(
+            area.polygons()
+                .map(|(outer, inner)| Polygon {
+                    outer: geometry::locations(outer).collect(),
+                    inner: inner
+                        .map(|inner| geometry::locations(inner).collect())
+                        .collect(),
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Boundary {
+    /// Serialize the boundary as GeoJSON `MultiPolygon` geometry
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+
+        let coordinates: Vec<Vec<Vec<[f64; 2]>>> = self
+            .polygons
+            .iter()
+            .map(|polygon| {
+                std::iter::once(&polygon.outer)
+                    .chain(&polygon.inner)
+                    .map(|ring| {
+                        closed(ring)
+                            .map(|location| [location.lon(), location.lat()])
+                            .collect()
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut state = serializer.serialize_struct("MultiPolygon", 2)?;
+        state.serialize_field("type", "MultiPolygon")?;
+        state.serialize_field("coordinates", &coordinates)?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Boundary {
+    /// Deserialize a boundary from GeoJSON
+    ///
+    /// Supported are `Polygon` and `MultiPolygon` geometries as well as
+    /// `Feature`, `FeatureCollection` and `GeometryCollection` objects whose polygons are all combined.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        /// A GeoJSON position with optional altitude
+        type Position = Vec<f64>;
+
+        /// The subset of GeoJSON objects which can contain polygons
+        #[derive(serde::Deserialize)]
+        #[serde(tag = "type")]
+        enum GeoJson {
+            Polygon {
+                coordinates: Vec<Vec<Position>>,
+            },
+            MultiPolygon {
+                coordinates: Vec<Vec<Vec<Position>>>,
+            },
+            Feature {
+                geometry: Option<Box<GeoJson>>,
+            },
+            FeatureCollection {
+                features: Vec<GeoJson>,
+            },
+            GeometryCollection {
+                geometries: Vec<GeoJson>,
+            },
+        }
+
+        /// Convert a GeoJSON polygon's rings, the first being the outer one
+        fn polygon<E: Error>(rings: Vec<Vec<Position>>) -> Result<Polygon, E> {
+            let mut rings = rings.into_iter().map(|ring| {
+                ring.into_iter()
+                    .map(|position| match position[..] {
+                        [lon, lat, ..] => Ok(Location::new(lon, lat)),
+                        _ => Err(E::custom("position with less than 2 coordinates")),
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            });
+            let outer = rings
+                .next()
+                .ok_or_else(|| E::custom("polygon without rings"))??;
+            Ok(Polygon {
+                outer,
+                inner: rings.collect::<Result<_, _>>()?,
+            })
+        }
+
+        /// Collect the polygons of a GeoJSON object
+        fn collect<E: Error>(geojson: GeoJson, polygons: &mut Vec<Polygon>) -> Result<(), E> {
+            match geojson {
+                GeoJson::Polygon { coordinates } => polygons.push(polygon(coordinates)?),
+                GeoJson::MultiPolygon { coordinates } => {
+                    for rings in coordinates {
+                        polygons.push(polygon(rings)?);
+                    }
+                }
+                GeoJson::Feature { geometry } => {
+                    if let Some(geometry) = geometry {
+                        collect(*geometry, polygons)?;
+                    }
+                }
+                GeoJson::FeatureCollection { features: children }
+                | GeoJson::GeometryCollection {
+                    geometries: children,
+                } => {
+                    for child in children {
+                        collect(child, polygons)?;
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        let mut polygons = Vec::new();
+        collect(GeoJson::deserialize(deserializer)?, &mut polygons)?;
+        Ok(Boundary::new(polygons))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLY: &str = "berlin
+1
+\t13.0000000\t52.0000000
+\t14.0000000\t52.0000000
+\t14.0000000\t53.0000000
+\t13.0000000\t53.0000000
+\t13.0000000\t52.0000000
+END
+!2
+\t13.4000000\t52.4000000
+\t13.6000000\t52.4000000
+\t13.6000000\t52.6000000
+\t13.4000000\t52.4000000
+END
+3
+\t15.0000000\t52.0000000
+\t16.0000000\t52.0000000
+\t15.5000000\t53.0000000
+\t15.0000000\t52.0000000
+END
+END
+";
+
+    #[test]
+    fn poly_round_trip() {
+        let boundary = Boundary::from_poly(POLY).unwrap();
+        assert_eq!(boundary.polygons().len(), 2);
+        assert_eq!(boundary.polygons()[0].inner.len(), 1);
+        assert_eq!(
+            boundary.envelope(),
+            BBox::from_degrees(13.0, 52.0, 16.0, 53.0)
+        );
+        assert!(boundary.contains(Location::new(13.2, 52.2)));
+        assert!(!boundary.contains(Location::new(13.55, 52.45)));
+        assert!(boundary.contains(Location::new(15.5, 52.5)));
+        assert!(!boundary.contains(Location::new(14.5, 52.5)));
+
+        assert_eq!(boundary.to_poly("berlin"), POLY);
+        assert_eq!(
+            Boundary::from_poly(&boundary.to_poly("berlin")).unwrap(),
+            boundary
+        );
+    }
+
+    #[test]
+    fn poly_with_blank_lines_and_unclosed_rings() {
+        let boundary =
+            Boundary::from_poly("name\n\n  1\n 1 1\n2 1\n  2 2  \nEND\nEND\n\n").unwrap();
+        assert_eq!(
+            boundary.polygons()[0].outer,
+            [
+                Location::new(1.0, 1.0),
+                Location::new(2.0, 1.0),
+                Location::new(2.0, 2.0)
+            ]
+        );
+        assert!(boundary
+            .to_poly("name")
+            .contains("\t1.0000000\t1.0000000\nEND"));
+    }
+
+    #[test]
+    fn poly_errors() {
+        let line = |text: &str| Boundary::from_poly(text).unwrap_err().line();
+        assert_eq!(line(""), 1);
+        assert_eq!(line("name\n"), 0);
+        assert_eq!(line("name\n1\n1 1\n"), 0);
+        assert_eq!(line("name\n1\n1 1\nEND\n"), 0);
+        assert_eq!(line("name\n1\n1 1\n1 x\nEND\nEND\n"), 4);
+        assert_eq!(line("name\n1\n1 1 1\nEND\nEND\n"), 3);
+        assert_eq!(line("name\n1\n1\nEND\nEND\n"), 3);
+        assert_eq!(line("name\n\n!1\n1 1\nEND\nEND\n"), 3);
+
+        let error = Boundary::from_poly("name\n1\n1 x\nEND\nEND\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid polygon file in line 3: expected longitude and latitude"
+        );
+    }
+
+    #[test]
+    fn from_bbox() {
+        let bbox = BBox::from_degrees(1.0, 2.0, 3.0, 4.0);
+        let boundary = Boundary::from(bbox);
+        assert_eq!(boundary.envelope(), bbox);
+        assert!(boundary.contains(Location::new(2.0, 3.0)));
+        assert!(!boundary.contains(Location::new(0.0, 3.0)));
+        assert_eq!(Boundary::from(BBox::new()), Boundary::default());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn geojson() {
+        let polygon = r#"{"type": "Polygon", "coordinates": [
+            [[13, 52], [14, 52], [14, 53], [13, 53], [13, 52]],
+            [[13.4, 52.4], [13.6, 52.4], [13.6, 52.6, 100], [13.4, 52.4]]
+        ]}"#;
+        let boundary: Boundary = serde_json::from_str(polygon).unwrap();
+        let mut polygons = Boundary::from_poly(POLY).unwrap().into_polygons();
+        polygons.truncate(1);
+        assert_eq!(boundary, Boundary::new(polygons));
+
+        let collection = format!(
+            r#"{{"type": "FeatureCollection", "features": [
+                {{"type": "Feature", "properties": {{"name": "berlin"}}, "geometry": {polygon}}},
+                {{"type": "Feature", "properties": null, "geometry": null}},
+                {{"type": "Feature", "geometry": {{"type": "MultiPolygon", "coordinates": [
+                    [[[15, 52], [16, 52], [15.5, 53], [15, 52]]]
+                ]}}}}
+            ]}}"#
+        );
+        let boundary: Boundary = serde_json::from_str(&collection).unwrap();
+        assert_eq!(boundary, Boundary::from_poly(POLY).unwrap());
+
+        let serialized = serde_json::to_string(&boundary).unwrap();
+        assert!(serialized.starts_with(r#"{"type":"MultiPolygon","coordinates":[[[[13.0,52.0],"#));
+        assert_eq!(
+            serde_json::from_str::<Boundary>(&serialized).unwrap(),
+            boundary
+        );
+
+        let empty: Boundary =
+            serde_json::from_str(r#"{"type": "Feature", "geometry": null}"#).unwrap();
+        assert_eq!(empty, Boundary::default());
+
+        assert!(
+            serde_json::from_str::<Boundary>(r#"{"type": "Point", "coordinates": [1, 2]}"#)
+                .is_err()
+        );
+        assert!(
+            serde_json::from_str::<Boundary>(r#"{"type": "Polygon", "coordinates": []}"#).is_err()
+        );
+        assert!(
+            serde_json::from_str::<Boundary>(r#"{"type": "Polygon", "coordinates": [[[1]]]}"#)
+                .is_err()
+        );
+    }
+}
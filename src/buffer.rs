@@ -1,13 +1,45 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::ffi::c_void;
+use std::fmt::{Display, Formatter};
 use std::iter::FusedIterator;
+use std::marker::PhantomData;
 
-use crate::item::Item;
+use crate::area::Area;
+use crate::item::{Item, ItemSubclass, ItemType, ALIGN_BYTES};
+use crate::node::Node;
+use crate::object::OSMObject;
+use crate::object_order;
+use crate::relation::Relation;
+use crate::way::Way;
 
 /// Buffer to clone OSM items into
+///
+/// The items are stored continuously just like in one of libosmium's buffers.
+/// The underlying memory is aligned to 8 bytes, just as the items expect it to be.
 #[derive(Clone, Default)]
 pub struct ItemBuffer {
-    buffer: Vec<u8>,
+    buffer: Vec<Block>,
+}
+
+/// Callback receiving a libosmium buffer's committed memory
+///
+/// Returning `false` asks c++ to stop producing buffers.
+pub(crate) type BufferCallback =
+    extern "C" fn(context: *mut c_void, data: *const u8, size: usize) -> bool;
+
+/// [BufferCallback] appending the items to the [ItemBuffer] passed as `context`
+pub(crate) extern "C" fn extend_buffer(context: *mut c_void, data: *const u8, size: usize) -> bool {
+    let buffer = unsafe { &mut *(context as *mut ItemBuffer) };
+    buffer.extend_from_bytes(unsafe { std::slice::from_raw_parts(data, size) });
+    true
 }
 
+/// Unit of memory the buffer is allocated in to guarantee the items' alignment
+#[repr(C, align(8))]
+#[derive(Copy, Clone)]
+struct Block([u8; ALIGN_BYTES]);
+
 impl ItemBuffer {
     /// Construct a new, empty buffer.
     pub const fn new() -> Self {
@@ -19,28 +51,235 @@ impl ItemBuffer {
     /// Note the capacity is in bytes not number of items, since they are all dynamically sized.
     pub fn with_capacity(size: usize) -> Self {
         Self {
-            buffer: Vec::with_capacity(size),
+            buffer: Vec::with_capacity(size.div_ceil(ALIGN_BYTES)),
+        }
+    }
+
+    /// Construct a buffer from bytes previously obtained from [`as_bytes`](ItemBuffer::as_bytes).
+    ///
+    /// Since the bytes might come from an untrusted source (for example a file on disk),
+    /// they are checked to contain a sequence of well formed items.
+    /// This covers the items' sizes and types as well as the layout of their sub items
+    /// (tags, node references, relation members, rings, etc.).
+    ///
+    /// Only nodes, ways, relations, areas and their sub items are supported.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, InvalidItem> {
+        let mut offset = 0;
+        while offset < bytes.len() {
+            let size = validate_item(&bytes[offset..], true)
+                .map_err(|(at, reason)| InvalidItem::new(offset + at, reason))?;
+            offset += size;
         }
+
+        Ok(unsafe { ItemBuffer::from_bytes_unchecked(bytes) })
+    }
+
+    /// Construct a buffer from bytes without validating them.
+    ///
+    /// # Safety
+    ///
+    /// The bytes have to be a sequence of well formed items, for example the content of a libosmium buffer.
+    pub(crate) unsafe fn from_bytes_unchecked(bytes: &[u8]) -> Self {
+        let mut buffer = ItemBuffer::with_capacity(bytes.len());
+        buffer.extend_from_bytes(bytes);
+        buffer
+    }
+
+    /// Get the buffer's items as continuous bytes.
+    ///
+    /// Use [`from_bytes`](ItemBuffer::from_bytes) to load them back into a buffer.
+    /// Note that the bytes use the platform's endianness.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            std::slice::from_raw_parts(
+                self.buffer.as_ptr() as *const u8,
+                self.buffer.len() * ALIGN_BYTES,
+            )
+        }
+    }
+
+    /// Mutable version of [`as_bytes`](ItemBuffer::as_bytes) for internal use
+    fn as_bytes_mut(&mut self) -> &mut [u8] {
+        unsafe {
+            std::slice::from_raw_parts_mut(
+                self.buffer.as_mut_ptr() as *mut u8,
+                self.buffer.len() * ALIGN_BYTES,
+            )
+        }
+    }
+
+    /// Append raw bytes whose length is a multiple of `ALIGN_BYTES`
+    fn extend_from_bytes(&mut self, bytes: &[u8]) {
+        self.buffer.extend(
+            bytes
+                .chunks_exact(ALIGN_BYTES)
+                .map(|chunk| Block(chunk.try_into().unwrap())),
+        );
     }
 
     /// Appends an item to the back of the buffer.
     pub fn push(&mut self, item: &impl AsRef<Item>) {
-        self.buffer.extend_from_slice(item.as_ref().as_bytes());
+        self.extend_from_bytes(item.as_ref().as_bytes());
+    }
+
+    /// Get the item starting at an offset in bytes.
+    ///
+    /// # Safety
+    ///
+    /// The offset has to be the start of an item, for example the buffer's length in bytes before the item was pushed.
+    pub(crate) unsafe fn get_unchecked(&self, offset: usize) -> &Item {
+        &*(self.as_bytes()[offset..].as_ptr() as *const Item)
     }
 
     /// Returns an iterator over the buffer.
     ///
     /// The iterator yields all items from start to end.
-    pub fn iter(&self) -> ItemBufferIterator {
+    pub fn iter(&self) -> ItemBufferIterator<'_> {
         ItemBufferIterator {
             buffer: self,
             index: 0,
         }
     }
 
+    /// Returns an iterator over all items of a specific subclass.
+    ///
+    /// This mirrors libosmium's `Buffer::select<T>()`.
+    pub fn select<T: ItemSubclass>(&self) -> Select<'_, T> {
+        Select {
+            items: self.iter(),
+            subclass: PhantomData,
+        }
+    }
+
+    /// Returns an iterator over all [nodes](Node).
+    pub fn nodes(&self) -> Select<'_, Node> {
+        self.select()
+    }
+
+    /// Returns an iterator over all [ways](Way).
+    pub fn ways(&self) -> Select<'_, Way> {
+        self.select()
+    }
+
+    /// Returns an iterator over all [relations](Relation).
+    pub fn relations(&self) -> Select<'_, Relation> {
+        self.select()
+    }
+
+    /// Returns an iterator over all [areas](Area).
+    pub fn areas(&self) -> Select<'_, Area> {
+        self.select()
+    }
+
+    /// Returns an iterator that allows modifying each item.
+    ///
+    /// The iterator yields all items from start to end.
+    pub fn iter_mut(&mut self) -> ItemBufferIteratorMut<'_> {
+        let range = self.as_bytes_mut().as_mut_ptr_range();
+        ItemBufferIteratorMut {
+            current: range.start,
+            end: range.end,
+            buffer_lifetime: PhantomData,
+        }
+    }
+
+    /// Retains only the items specified by the predicate.
+    ///
+    /// In other words, remove all items `i` for which `f(&i)` returns `false`.
+    /// This method operates in place and preserves the order of the retained items.
+    pub fn retain(&mut self, mut f: impl FnMut(&Item) -> bool) {
+        let bytes = self.as_bytes_mut();
+        let mut read = 0;
+        let mut write = 0;
+        while read < bytes.len() {
+            let item = unsafe { &*(bytes.as_ptr().add(read) as *const Item) };
+            let size = item.aligned_size();
+            if f(item) {
+                bytes.copy_within(read..read + size, write);
+                write += size;
+            }
+            read += size;
+        }
+        self.buffer.truncate(write / ALIGN_BYTES);
+    }
+
+    /// Removes all items which are [marked as removed](Item::removed).
+    pub fn purge_removed(&mut self) {
+        self.retain(|item| !item.removed());
+    }
+
+    /// Sort the buffer's osm objects in the [canonical order](object_order::object_order_type_id_version)
+    /// i.e. by type, id and version.
+    ///
+    /// Items which aren't osm objects are moved to the end.
+    pub fn sort(&mut self) {
+        self.sort_by(object_order::object_order_type_id_version);
+    }
+
+    /// Sort the buffer's osm objects using a comparison function.
+    ///
+    /// The sort is stable. Items which aren't osm objects are moved to the end.
+    /// Like for [`slice::sort_by`] the function has to be a total order.
+    pub fn sort_by(&mut self, mut compare: impl FnMut(&OSMObject, &OSMObject) -> Ordering) {
+        let mut items: Vec<&Item> = self.iter().collect();
+        items.sort_by(|lhs, rhs| {
+            match (lhs.downcast::<OSMObject>(), rhs.downcast::<OSMObject>()) {
+                (Some(lhs), Some(rhs)) => compare(lhs, rhs),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+        });
+
+        let mut sorted = ItemBuffer::with_capacity(self.as_bytes().len());
+        for item in items {
+            sorted.push(item);
+        }
+        *self = sorted;
+    }
+
+    /// Remove consecutive osm objects with the [same type, id and version](object_order::object_equal_type_id_version).
+    ///
+    /// Only the first of the duplicates is kept. If the buffer is [sorted](ItemBuffer::sort),
+    /// this removes all duplicates.
+    pub fn dedup(&mut self) {
+        let mut previous: Option<&OSMObject> = None;
+        let keep: Vec<bool> = self
+            .iter()
+            .map(|item| match item.downcast::<OSMObject>() {
+                Some(object) => {
+                    let duplicate = previous.is_some_and(|previous| {
+                        object_order::object_equal_type_id_version(previous, object)
+                    });
+                    previous = Some(object);
+                    !duplicate
+                }
+                None => true,
+            })
+            .collect();
+
+        let mut keep = keep.into_iter();
+        self.retain(|_| keep.next().unwrap_or(true));
+    }
+
+    /// Merge buffers which are each [sorted](ItemBuffer::sort) into a new sorted buffer.
+    ///
+    /// Objects with the [same type, id and version](object_order::object_equal_type_id_version)
+    /// in multiple buffers are only included once. Items which aren't osm objects are ignored.
+    pub fn merge<'a>(buffers: impl IntoIterator<Item = &'a ItemBuffer>) -> ItemBuffer {
+        object_order::merge(buffers)
+    }
+
     /// Check whether an item fits into the buffer's remaining space without reallocating.
     pub fn fits(&self, item: &impl AsRef<Item>) -> bool {
-        self.buffer.capacity() - self.buffer.len() > item.as_ref().aligned_size()
+        (self.buffer.capacity() - self.buffer.len()) * ALIGN_BYTES >= item.as_ref().aligned_size()
+    }
+
+    /// Returns the number of items in the buffer.
+    ///
+    /// Since items are dynamically sized, this requires iterating over the entire buffer.
+    pub fn len(&self) -> usize {
+        self.iter().count()
     }
 
     /// Return true if the buffer contains no items.
@@ -65,6 +304,21 @@ impl std::fmt::Debug for ItemBuffer {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for ItemBuffer {
+    /// Serialize the buffer as sequence of its items
+    ///
+    /// See [`Item`'s implementation](Item#impl-Serialize-for-Item) for which items are supported.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for item in self {
+            seq.serialize_element(item)?;
+        }
+        seq.end()
+    }
+}
+
 /// Immutable [ItemBuffer] iterator
 ///
 /// This struct is created by the [`iter`](ItemBuffer::iter) method.
@@ -75,12 +329,21 @@ pub struct ItemBufferIterator<'b> {
     index: usize,
 }
 
+impl ItemBufferIterator<'_> {
+    /// Get the offset in bytes of the next item in the buffer
+    ///
+    /// The offset of an item is the buffer's length in bytes before the item was pushed.
+    pub fn offset(&self) -> usize {
+        self.index
+    }
+}
+
 impl<'b> Iterator for ItemBufferIterator<'b> {
     type Item = &'b Item;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let item: &u8 = self.buffer.buffer.get(self.index)?;
-        let item: &Item = unsafe { std::mem::transmute(item) };
+        let item: &u8 = self.buffer.as_bytes().get(self.index)?;
+        let item = unsafe { &*(item as *const u8 as *const Item) };
         self.index += item.aligned_size();
         Some(item)
     }
@@ -95,3 +358,558 @@ impl<'b> IntoIterator for &'b ItemBuffer {
         self.iter()
     }
 }
+
+/// [ItemBuffer] iterator over a specific subclass of items
+///
+/// This struct is created by the [`select`](ItemBuffer::select) method and its shorthands.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Select<'b, T> {
+    items: ItemBufferIterator<'b>,
+    subclass: PhantomData<&'b T>,
+}
+
+impl<'b, T: ItemSubclass + 'b> Iterator for Select<'b, T> {
+    type Item = &'b T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.items.find_map(Item::downcast)
+    }
+}
+impl<'b, T: ItemSubclass + 'b> FusedIterator for Select<'b, T> {}
+
+impl<T> Clone for Select<'_, T> {
+    fn clone(&self) -> Self {
+        Select {
+            items: self.items,
+            subclass: PhantomData,
+        }
+    }
+}
+
+/// Mutable [ItemBuffer] iterator
+///
+/// This struct is created by the [`iter_mut`](ItemBuffer::iter_mut) method.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct ItemBufferIteratorMut<'b> {
+    current: *mut u8,
+    end: *mut u8,
+    buffer_lifetime: PhantomData<&'b mut ItemBuffer>,
+}
+
+impl<'b> Iterator for ItemBufferIteratorMut<'b> {
+    type Item = &'b mut Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current == self.end {
+            return None;
+        }
+
+        let item: &mut Item = unsafe { &mut *(self.current as *mut Item) };
+        self.current = unsafe { self.current.add(item.aligned_size()) };
+        Some(item)
+    }
+}
+impl<'b> FusedIterator for ItemBufferIteratorMut<'b> {}
+
+impl<'b> IntoIterator for &'b mut ItemBuffer {
+    type Item = &'b mut Item;
+    type IntoIter = ItemBufferIteratorMut<'b>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+/// An [ItemBuffer] which only contains items of a single subclass
+///
+/// Because every item is known to be a `T`, iterating doesn't require any casting.
+pub struct TypedBuffer<T> {
+    buffer: ItemBuffer,
+    subclass: PhantomData<T>,
+}
+
+impl<T: ItemSubclass> TypedBuffer<T> {
+    /// Construct a new, empty buffer.
+    pub const fn new() -> Self {
+        Self {
+            buffer: ItemBuffer::new(),
+            subclass: PhantomData,
+        }
+    }
+
+    /// Constructs a new, empty buffer with at least the specified capacity.
+    ///
+    /// Note the capacity is in bytes not number of items, since they are all dynamically sized.
+    pub fn with_capacity(size: usize) -> Self {
+        Self {
+            buffer: ItemBuffer::with_capacity(size),
+            subclass: PhantomData,
+        }
+    }
+
+    /// Appends an item to the back of the buffer.
+    pub fn push(&mut self, item: &T) {
+        self.buffer.push(item);
+    }
+
+    /// Returns an iterator over the buffer.
+    ///
+    /// The iterator yields all items from start to end.
+    pub fn iter(&self) -> Select<'_, T> {
+        self.buffer.select()
+    }
+
+    /// Returns an iterator that allows modifying each item.
+    ///
+    /// The iterator yields all items from start to end.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.buffer.iter_mut().filter_map(Item::downcast_mut)
+    }
+
+    /// Retains only the items specified by the predicate.
+    ///
+    /// See [`ItemBuffer::retain`].
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        self.buffer
+            .retain(|item| item.downcast().map(&mut f).unwrap_or(false));
+    }
+
+    /// Returns the number of items in the buffer.
+    ///
+    /// Since items are dynamically sized, this requires iterating over the entire buffer.
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Return true if the buffer contains no items.
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+
+    /// Clears the whole buffer, removing all items.
+    pub fn clear(&mut self) {
+        self.buffer.clear()
+    }
+
+    /// Get the underlying untyped buffer.
+    pub fn as_item_buffer(&self) -> &ItemBuffer {
+        &self.buffer
+    }
+
+    /// Unwrap the underlying untyped buffer.
+    pub fn into_item_buffer(self) -> ItemBuffer {
+        self.buffer
+    }
+}
+
+impl<T: ItemSubclass> TryFrom<ItemBuffer> for TypedBuffer<T> {
+    type Error = ItemBuffer;
+
+    /// Use an untyped buffer as typed buffer, if it only contains items of type `T`.
+    ///
+    /// Otherwise, the untyped buffer is returned as error.
+    fn try_from(buffer: ItemBuffer) -> Result<Self, Self::Error> {
+        if buffer.iter().all(|item| T::is_instance(item.item_type())) {
+            Ok(TypedBuffer {
+                buffer,
+                subclass: PhantomData,
+            })
+        } else {
+            Err(buffer)
+        }
+    }
+}
+
+impl<T: ItemSubclass> Default for TypedBuffer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for TypedBuffer<T> {
+    fn clone(&self) -> Self {
+        Self {
+            buffer: self.buffer.clone(),
+            subclass: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for TypedBuffer<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.buffer.fmt(f)
+    }
+}
+
+impl<'b, T: ItemSubclass + 'b> IntoIterator for &'b TypedBuffer<T> {
+    type Item = &'b T;
+    type IntoIter = Select<'b, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T> serde::Serialize for TypedBuffer<T> {
+    /// Serialize the buffer as sequence of its items
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.buffer.serialize(serializer)
+    }
+}
+
+/// Error returned by [`ItemBuffer::from_bytes`] when the bytes don't contain well formed items
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InvalidItem {
+    offset: usize,
+    reason: &'static str,
+}
+
+impl InvalidItem {
+    fn new(offset: usize, reason: &'static str) -> Self {
+        InvalidItem { offset, reason }
+    }
+
+    /// Get the byte offset the malformed data was found at
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
+
+impl Display for InvalidItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid item at byte {}: {}", self.offset, self.reason)
+    }
+}
+
+impl Error for InvalidItem {}
+
+// The following sizes are taken from libosmium's class definitions.
+
+/// `sizeof(osmium::memory::Item)`
+const ITEM_SIZE: usize = 8;
+
+/// `sizeof(osmium::OSMObject)`
+const OBJECT_SIZE: usize = 32;
+
+/// `sizeof(osmium::Location)` which is stored after the [`OBJECT_SIZE`] in a node
+const LOCATION_SIZE: usize = 8;
+
+/// `sizeof(osmium::string_size_type)`
+const STRING_SIZE_SIZE: usize = 2;
+
+/// `sizeof(osmium::NodeRef)`
+const NODE_REF_SIZE: usize = 16;
+
+/// `sizeof(osmium::RelationMember)`
+const RELATION_MEMBER_SIZE: usize = 16;
+
+/// Result of validation: either the validated size or an offset and reason
+type Validation = Result<usize, (usize, &'static str)>;
+
+fn padded(size: usize) -> usize {
+    (size + ALIGN_BYTES - 1) & !(ALIGN_BYTES - 1)
+}
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_ne_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+/// Validate the item at the start of `bytes` and return its aligned size
+///
+/// `top_level` selects whether an osm object or a sub item is expected.
+fn validate_item(bytes: &[u8], top_level: bool) -> Validation {
+    if bytes.len() < ITEM_SIZE {
+        return Err((0, "not enough bytes for an item header"));
+    }
+    let size = u32::from_ne_bytes(bytes[0..4].try_into().unwrap()) as usize;
+    if size < ITEM_SIZE {
+        return Err((0, "item is smaller than its header"));
+    }
+    if padded(size) > bytes.len() {
+        return Err((0, "item is larger than the remaining bytes"));
+    }
+    let item_type = ItemType::try_from(read_u16(bytes, 4)).map_err(|_| (4, "unknown item type"))?;
+    let bytes = &bytes[..padded(size)];
+
+    match (top_level, item_type) {
+        (true, ItemType::Node) => validate_object(bytes, size, true, &[ItemType::TagList]),
+        (true, ItemType::Way) => validate_object(
+            bytes,
+            size,
+            false,
+            &[ItemType::TagList, ItemType::WayNodeList],
+        ),
+        (true, ItemType::Relation) => validate_object(
+            bytes,
+            size,
+            false,
+            &[ItemType::TagList, ItemType::RelationMemberList],
+        ),
+        (true, ItemType::Area) => validate_object(
+            bytes,
+            size,
+            false,
+            &[ItemType::TagList, ItemType::OuterRing, ItemType::InnerRing],
+        ),
+        (false, ItemType::TagList) => validate_tag_list(&bytes[ITEM_SIZE..size]),
+        (false, ItemType::WayNodeList | ItemType::OuterRing | ItemType::InnerRing) => {
+            match (size - ITEM_SIZE) % NODE_REF_SIZE {
+                0 => Ok(()),
+                _ => Err((0, "node ref list's size is not a multiple of a node ref's")),
+            }
+        }
+        (false, ItemType::RelationMemberList) => validate_members(&bytes[ITEM_SIZE..size])
+            .map_err(|(offset, reason)| (ITEM_SIZE + offset, reason)),
+        _ => Err((4, "unsupported item type")),
+    }?;
+    Ok(bytes.len())
+}
+
+/// Validate an osm object's metadata, user name and sub items
+fn validate_object(
+    bytes: &[u8],
+    size: usize,
+    is_node: bool,
+    sub_items: &[ItemType],
+) -> Result<(), (usize, &'static str)> {
+    let user_position = OBJECT_SIZE + if is_node { LOCATION_SIZE } else { 0 };
+    let user_start = user_position + STRING_SIZE_SIZE;
+    if size < user_start {
+        return Err((0, "object is smaller than its header"));
+    }
+
+    let user_size = read_u16(bytes, user_position) as usize;
+    if user_size == 0 || user_start + user_size > size {
+        return Err((user_position, "invalid user name size"));
+    }
+    if bytes[user_start + user_size - 1] != 0 {
+        return Err((user_start, "user name is not NUL terminated"));
+    }
+
+    let mut offset = padded(user_start + user_size);
+    while offset < bytes.len() {
+        let sub_item = &bytes[offset..];
+        let sub_size =
+            validate_item(sub_item, false).map_err(|(at, reason)| (offset + at, reason))?;
+        if !sub_items.contains(&ItemType::try_from(read_u16(sub_item, 4)).unwrap()) {
+            return Err((offset + 4, "unexpected sub item type"));
+        }
+        offset += sub_size;
+    }
+    Ok(())
+}
+
+/// Validate a tag list's data i.e. pairs of NUL terminated strings
+fn validate_tag_list(data: &[u8]) -> Result<(), (usize, &'static str)> {
+    let terminators = data.iter().filter(|byte| **byte == 0).count();
+    if data.last().is_some_and(|byte| *byte != 0) || terminators % 2 != 0 {
+        Err((
+            ITEM_SIZE,
+            "tag list doesn't consist of NUL terminated pairs",
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Validate a relation member list's data i.e. member references with their roles
+fn validate_members(data: &[u8]) -> Result<(), (usize, &'static str)> {
+    let mut offset = 0;
+    while offset < data.len() {
+        let member = &data[offset..];
+        if member.len() < RELATION_MEMBER_SIZE {
+            return Err((offset, "not enough bytes for a relation member"));
+        }
+        if ItemType::try_from(read_u16(member, 8)).is_err() {
+            return Err((offset + 8, "unknown member type"));
+        }
+        if read_u16(member, 10) != 0 {
+            return Err((
+                offset + 10,
+                "relation members with full members are not supported",
+            ));
+        }
+
+        let role_size = read_u16(member, 12) as usize;
+        let member_size = padded(RELATION_MEMBER_SIZE + role_size);
+        if role_size == 0 || member_size > member.len() {
+            return Err((offset + 12, "invalid role size"));
+        }
+        if member[RELATION_MEMBER_SIZE + role_size - 1] != 0 {
+            return Err((offset + RELATION_MEMBER_SIZE, "role is not NUL terminated"));
+        }
+        offset += member_size;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::location::Location;
+
+    /// Append a NUL padded item with a header of `item_type` and `data` to `bytes`
+    fn push_item(bytes: &mut Vec<u8>, item_type: ItemType, data: &[u8]) {
+        let size = (ITEM_SIZE + data.len()) as u32;
+        bytes.extend_from_slice(&size.to_ne_bytes());
+        bytes.extend_from_slice(&(item_type as u16).to_ne_bytes());
+        bytes.extend_from_slice(&[0, 0]);
+        bytes.extend_from_slice(data);
+        bytes.resize(padded(bytes.len()), 0);
+    }
+
+    /// Serialize an osm object with a user name like libosmium would
+    ///
+    /// `fields` are the subclass' fields following the object's and `tags` the content of its tag list.
+    fn object(
+        item_type: ItemType,
+        id: i64,
+        fields: &[u8],
+        tags: &[u8],
+        sub_item: Option<(ItemType, &[u8])>,
+    ) -> Vec<u8> {
+        let mut data = vec![0; OBJECT_SIZE - ITEM_SIZE];
+        data[..8].copy_from_slice(&id.to_ne_bytes());
+        data.extend_from_slice(fields);
+        let user = b"user\0";
+        data.extend_from_slice(&(user.len() as u16).to_ne_bytes());
+        data.extend_from_slice(user);
+        data.resize(padded(ITEM_SIZE + data.len()) - ITEM_SIZE, 0);
+        push_item(&mut data, ItemType::TagList, tags);
+        if let Some((sub_type, sub_data)) = sub_item {
+            push_item(&mut data, sub_type, sub_data);
+        }
+
+        let mut bytes = Vec::new();
+        push_item(&mut bytes, item_type, &data);
+        bytes
+    }
+
+    /// Serialize a node with a user name and a tag list like libosmium would
+    pub(crate) fn node(id: i64, tags: &[u8]) -> Vec<u8> {
+        object(ItemType::Node, id, &[0; LOCATION_SIZE], tags, None)
+    }
+
+    /// Serialize an untagged node at a location
+    pub(crate) fn located_node(id: i64, location: Location) -> Vec<u8> {
+        let mut fields = location.raw_x.to_ne_bytes().to_vec();
+        fields.extend_from_slice(&location.raw_y.to_ne_bytes());
+        object(ItemType::Node, id, &fields, b"", None)
+    }
+
+    /// Serialize an untagged way referencing nodes without locations
+    pub(crate) fn way(id: i64, nodes: &[i64]) -> Vec<u8> {
+        let mut refs = Vec::new();
+        for node in nodes {
+            refs.extend_from_slice(&node.to_ne_bytes());
+            refs.extend_from_slice(&[0xff, 0xff, 0xff, 0x7f].repeat(2));
+        }
+        object(
+            ItemType::Way,
+            id,
+            &[],
+            b"",
+            Some((ItemType::WayNodeList, &refs)),
+        )
+    }
+
+    /// Serialize a relation with a tag list and members with empty roles
+    pub(crate) fn relation(id: i64, tags: &[u8], members: &[(ItemType, i64)]) -> Vec<u8> {
+        let mut list = Vec::new();
+        for (member_type, member_ref) in members {
+            list.extend_from_slice(&member_ref.to_ne_bytes());
+            list.extend_from_slice(&(*member_type as u16).to_ne_bytes());
+            list.extend_from_slice(&[0, 0]);
+            list.extend_from_slice(&1u16.to_ne_bytes());
+            list.resize(list.len() + RELATION_MEMBER_SIZE - 14 + ALIGN_BYTES, 0);
+        }
+        object(
+            ItemType::Relation,
+            id,
+            &[],
+            tags,
+            Some((ItemType::RelationMemberList, &list)),
+        )
+    }
+
+    /// Set an item's size field
+    fn set_size(bytes: &mut [u8], offset: usize, size: u32) {
+        bytes[offset..offset + 4].copy_from_slice(&size.to_ne_bytes());
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut bytes = node(1, b"amenity\0cafe\0");
+        bytes.extend(node(2, b""));
+
+        let buffer = ItemBuffer::from_bytes(&bytes).unwrap();
+        assert_eq!(buffer.as_bytes(), &bytes[..]);
+        assert_eq!(buffer.len(), 2);
+        assert!(buffer.iter().all(|item| item.item_type() == ItemType::Node));
+
+        let copy = ItemBuffer::from_bytes(buffer.as_bytes()).unwrap();
+        assert_eq!(copy.as_bytes(), buffer.as_bytes());
+    }
+
+    #[test]
+    fn empty() {
+        assert!(ItemBuffer::from_bytes(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn truncated_item() {
+        let bytes = node(1, b"amenity\0cafe\0");
+        let error = ItemBuffer::from_bytes(&bytes[..bytes.len() - ALIGN_BYTES]).unwrap_err();
+        assert_eq!(error.offset(), 0);
+
+        let error = ItemBuffer::from_bytes(&bytes[..4]).unwrap_err();
+        assert_eq!(error.offset(), 0);
+    }
+
+    #[test]
+    fn misaligned_size() {
+        // A way node list whose size isn't a multiple of a node ref's
+        let mut data = vec![0; OBJECT_SIZE - ITEM_SIZE];
+        data.extend_from_slice(&2u16.to_ne_bytes());
+        data.extend_from_slice(b"u\0");
+        data.resize(padded(ITEM_SIZE + data.len()) - ITEM_SIZE, 0);
+        let list = data.len() + ITEM_SIZE;
+        push_item(&mut data, ItemType::WayNodeList, &[0; NODE_REF_SIZE + 8]);
+        let mut bytes = Vec::new();
+        push_item(&mut bytes, ItemType::Way, &data);
+
+        let error = ItemBuffer::from_bytes(&bytes).unwrap_err();
+        assert_eq!(error.offset(), list);
+    }
+
+    #[test]
+    fn bad_item_type() {
+        let mut bytes = node(1, b"");
+        bytes[4..6].copy_from_slice(&0x7fu16.to_ne_bytes());
+        assert_eq!(ItemBuffer::from_bytes(&bytes).unwrap_err().offset(), 4);
+
+        // A sub item at the top level
+        let mut bytes = node(1, b"");
+        bytes[4..6].copy_from_slice(&(ItemType::TagList as u16).to_ne_bytes());
+        assert_eq!(ItemBuffer::from_bytes(&bytes).unwrap_err().offset(), 4);
+    }
+
+    #[test]
+    fn unterminated_tag() {
+        let mut bytes = node(1, b"amenity\0cafe");
+        // Drop the tag list's padding from its size, so the last byte isn't NUL
+        let tag_list = bytes.len() - padded(ITEM_SIZE + 12);
+        set_size(&mut bytes, tag_list, (ITEM_SIZE + 12) as u32);
+        let error = ItemBuffer::from_bytes(&bytes).unwrap_err();
+        assert_eq!(error.offset(), tag_list + ITEM_SIZE);
+    }
+
+    #[test]
+    fn odd_tag_terminators() {
+        let bytes = node(1, b"amenity\0cafe\0name\0");
+        let tag_list = bytes.len() - padded(ITEM_SIZE + 18);
+        let error = ItemBuffer::from_bytes(&bytes).unwrap_err();
+        assert_eq!(error.offset(), tag_list + ITEM_SIZE);
+    }
+}
@@ -0,0 +1,321 @@
+//! defines the [ChangeHandler] to process OsmChange (`.osc`) files.
+//!
+//! An OsmChange file groups its objects into `<create>`, `<modify>` and `<delete>` sections.
+//! libosmium doesn't keep these sections when parsing, it only marks deleted objects as [not visible](OSMObject::visible).
+//! Therefore, created and modified objects are told apart by their version:
+//! An object with version 1 can only have been created.
+//!
+//! This heuristic fails for objects which have been deleted and are restored by a later change.
+//! They are reported as [modified](ChangeHandler::modified) while the diff lists them as created.
+//!
+//! To update a base file with change files, use [ApplyChanges].
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::iter::Peekable;
+
+use crate::buffer::{ItemBuffer, Select};
+use crate::handler::{from_fns, Handler, Progress};
+use crate::io::{Error, File, Reader};
+use crate::item::ItemType;
+use crate::node::Node;
+use crate::object::{OSMObject, ObjectId, ObjectVersion};
+use crate::object_order::{
+    object_equal_type_id, object_equal_type_id_version, object_order_type_id_reverse_version,
+    object_order_type_id_version, ObjectPointerCollection,
+};
+use crate::relation::Relation;
+use crate::way::Way;
+
+/// The action an OsmChange file applies to an object
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Change {
+    /// The object has been created
+    Create,
+
+    /// A new version of the object replaces the old one
+    Modify,
+
+    /// The object has been deleted
+    Delete,
+}
+
+impl Change {
+    /// Guess an object's action using its visibility and version.
+    ///
+    /// See the [module's documentation](self) for the heuristic's limits.
+    pub fn of(object: &OSMObject) -> Self {
+        if !object.visible() {
+            Change::Delete
+        } else if object.version() == 1 {
+            Change::Create
+        } else {
+            Change::Modify
+        }
+    }
+}
+
+/// A change handler processes the objects of an OsmChange file grouped by their [Change].
+///
+/// Like [Handler] every method does nothing by default.
+/// To read a file, wrap the handler in [Changes] which implements [Handler]:
+///
+/// ```no_run
+/// use libosmium::change::{ChangeHandler, Changes};
+/// use libosmium::handler::Handler;
+/// use libosmium::OSMObject;
+///
+/// struct Database;
+/// impl ChangeHandler for Database {
+///     fn deleted(&mut self, object: &OSMObject) {
+///         println!("Delete {}", object.id());
+///     }
+/// }
+///
+/// Changes(Database).apply("minutely.osc.gz").unwrap();
+/// ```
+pub trait ChangeHandler {
+    /// Process a newly created object
+    fn created(&mut self, _object: &OSMObject) {}
+
+    /// Process a new version of an existing object
+    fn modified(&mut self, _object: &OSMObject) {}
+
+    /// Process a deleted object
+    ///
+    /// Depending on the file, the deleted object might not have any tags, nodes or members.
+    fn deleted(&mut self, _object: &OSMObject) {}
+
+    /// Finalize temporary or lazy data
+    fn flush(&mut self) {}
+
+    /// Process an object by calling the method of its [Change]
+    fn change(&mut self, object: &OSMObject) {
+        match Change::of(object) {
+            Change::Create => self.created(object),
+            Change::Modify => self.modified(object),
+            Change::Delete => self.deleted(object),
+        }
+    }
+}
+
+/// Adapter turning a [ChangeHandler] into a [Handler]
+///
+/// Only nodes, ways and relations are passed on, everything else (for example areas) is ignored.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Changes<C>(pub C);
+
+impl<C: ChangeHandler> Handler for Changes<C> {
+    fn node(&mut self, node: &Node) {
+        self.0.change(node);
+    }
+
+    fn way(&mut self, way: &Way) {
+        self.0.change(way);
+    }
+
+    fn relation(&mut self, relation: &Relation) {
+        self.0.change(relation);
+    }
+
+    fn flush(&mut self) {
+        self.0.flush();
+    }
+}
+
+impl<C: ChangeHandler + ?Sized> ChangeHandler for &mut C {
+    fn created(&mut self, object: &OSMObject) {
+        (**self).created(object);
+    }
+
+    fn modified(&mut self, object: &OSMObject) {
+        (**self).modified(object);
+    }
+
+    fn deleted(&mut self, object: &OSMObject) {
+        (**self).deleted(object);
+    }
+
+    fn flush(&mut self) {
+        (**self).flush();
+    }
+
+    fn change(&mut self, object: &OSMObject) {
+        (**self).change(object);
+    }
+}
+
+/// Apply OsmChange files to a base file like `osmium apply-changes`
+///
+/// The changes are read into memory first. Then the base file is read and its objects
+/// are passed to a [Handler] with the changes merged in.
+///
+/// By default, the output contains only the latest version of every object
+/// and deleted objects are removed. With [`with_history`](ApplyChanges::with_history)
+/// all versions (including deleted ones) are kept, which is meant for history files.
+///
+/// ```no_run
+/// use libosmium::change::ApplyChanges;
+/// use libosmium::handler::from_fns;
+/// use libosmium::io::File;
+///
+/// let mut nodes = 0;
+/// ApplyChanges::new(
+///     File::new("region.osm.pbf"),
+///     [File::new("day1.osc.gz"), File::new("day2.osc.gz")],
+/// )
+/// .apply(&mut from_fns().node(|_node| nodes += 1))?;
+/// # Ok::<(), libosmium::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct ApplyChanges<'a> {
+    base: File<'a>,
+    changes: Vec<File<'a>>,
+    history: bool,
+    sorted: bool,
+}
+
+impl<'a> ApplyChanges<'a> {
+    /// Apply the changes to the base file.
+    ///
+    /// If several change files contain the same version of an object, the earliest file's is used.
+    pub fn new(base: File<'a>, changes: impl IntoIterator<Item = File<'a>>) -> Self {
+        Self {
+            base,
+            changes: changes.into_iter().collect(),
+            history: false,
+            sorted: true,
+        }
+    }
+
+    /// Keep all versions of every object instead of only the latest one.
+    ///
+    /// Versions which exist in the base file and in a change are only passed on once.
+    pub fn with_history(mut self) -> Self {
+        self.history = true;
+        self
+    }
+
+    /// Don't assume the base file to be [sorted](crate::object_order::object_order_type_id_version).
+    ///
+    /// By default, the changes are merged into the base file's stream keeping it sorted.
+    /// An unsorted base file's objects are passed on in their original order,
+    /// skipping the ones replaced by a change. All changes are passed on at the end instead.
+    pub fn unsorted(mut self) -> Self {
+        self.sorted = false;
+        self
+    }
+
+    /// Read the files and process the resulting objects using a handler.
+    ///
+    /// The handler is [flushed](Handler::flush) once at the end.
+    pub fn apply<H: Handler>(self, handler: &mut H) -> Result<(), Error> {
+        let changes = read_changes(self.changes, self.history)?;
+        let mut merge = MergeChanges {
+            changes: changes.select::<OSMObject>().peekable(),
+            replaced: HashSet::new(),
+            history: self.history,
+            sorted: self.sorted,
+            handler,
+        };
+        if !self.sorted {
+            merge.replaced = changes
+                .select::<OSMObject>()
+                .map(|change| merge.key(change))
+                .collect();
+        }
+        Reader::new(self.base).apply(&mut merge)
+    }
+}
+
+/// Read all change files into a sorted buffer
+///
+/// Without history only the latest version of every object is kept.
+fn read_changes(changes: Vec<File<'_>>, history: bool) -> Result<ItemBuffer, Error> {
+    let mut buffer = ItemBuffer::new();
+    Reader::from_files(changes).apply(&mut from_fns().osm_object(|object| buffer.push(object)))?;
+
+    let mut objects = ObjectPointerCollection::from(&buffer);
+    if history {
+        objects.sort_by(object_order_type_id_version);
+        objects.unique_by(object_equal_type_id_version);
+    } else {
+        objects.sort_by(object_order_type_id_reverse_version);
+        objects.unique_by(object_equal_type_id);
+    }
+    Ok(objects.into_buffer())
+}
+
+/// Handler merging sorted changes into the base file's stream
+struct MergeChanges<'c, H> {
+    changes: Peekable<Select<'c, OSMObject>>,
+    /// Keys of all changes, if the base file is unsorted
+    replaced: HashSet<(ItemType, ObjectId, ObjectVersion)>,
+    history: bool,
+    sorted: bool,
+    handler: &'c mut H,
+}
+
+impl<H: Handler> MergeChanges<'_, H> {
+    /// Key identifying the objects a change replaces
+    ///
+    /// Without history a change replaces all versions, so the version is ignored.
+    fn key(&self, object: &OSMObject) -> (ItemType, ObjectId, ObjectVersion) {
+        let version = if self.history { object.version() } else { 0 };
+        (object.as_ref().item_type(), object.id(), version)
+    }
+
+    /// Does a change replace an object from the base file?
+    fn replaces(history: bool, change: &OSMObject, object: &OSMObject) -> bool {
+        if history {
+            object_equal_type_id_version(change, object)
+        } else {
+            object_equal_type_id(change, object)
+        }
+    }
+
+    /// Pass a change on to the handler, unless it is a deletion and history is not kept
+    fn emit_change(&mut self, change: &OSMObject) {
+        if self.history || change.visible() {
+            self.handler.apply_item(change.as_ref());
+        }
+    }
+}
+
+impl<H: Handler> Handler for MergeChanges<'_, H> {
+    fn osm_object(&mut self, object: &OSMObject) {
+        if !self.sorted {
+            if !self.replaced.contains(&self.key(object)) {
+                self.handler.apply_item(object.as_ref());
+            }
+            return;
+        }
+
+        let history = self.history;
+        while let Some(change) = self.changes.next_if(|change| {
+            !Self::replaces(history, change, object)
+                && object_order_type_id_version(change, object) == Ordering::Less
+        }) {
+            self.emit_change(change);
+        }
+
+        match self
+            .changes
+            .next_if(|change| Self::replaces(history, change, object))
+        {
+            Some(change) => self.emit_change(change),
+            None => self.handler.apply_item(object.as_ref()),
+        }
+    }
+
+    fn flush(&mut self) {
+        while let Some(change) = self.changes.next() {
+            self.emit_change(change);
+        }
+        self.handler.flush();
+    }
+
+    fn progress(&mut self, progress: &Progress) {
+        self.handler.progress(progress);
+    }
+}
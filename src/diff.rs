@@ -0,0 +1,341 @@
+//! defines the [DiffHandler] to process history files.
+//!
+//! A history file contains every version of an object sorted by [type, id and version](crate::object_order::object_order_type_id_version).
+//! The [DiffHandler] gets every version together with its neighbours as a [DiffObject],
+//! mimicking libosmium's `osmium::DiffHandler` and `osmium::DiffIterator`.
+//!
+//! To get the state of the data at a point in time, use [`time_filter`].
+
+use std::marker::PhantomData;
+use std::ptr;
+
+use crate::buffer::ItemBuffer;
+use crate::handler::Handler;
+use crate::item::ItemSubclass;
+use crate::node::Node;
+use crate::object::{OSMObject, ObjectId, ObjectVersion, Timestamp};
+use crate::object_order::object_equal_type_id;
+use crate::relation::Relation;
+use crate::way::Way;
+
+/// Timestamp after every other one, which ends the latest version's validity
+pub const END_OF_TIME: Timestamp = Timestamp::MAX;
+
+/// A version of an object together with the previous and the next version
+///
+/// The first version is its own previous one and the last version is its own next one.
+/// Each version is valid from its own timestamp until the next version's timestamp.
+pub struct DiffObject<'a, T = OSMObject> {
+    prev: &'a OSMObject,
+    curr: &'a OSMObject,
+    next: &'a OSMObject,
+    subclass: PhantomData<&'a T>,
+}
+
+impl<'a, T: ItemSubclass> DiffObject<'a, T> {
+    /// Get the previous version
+    pub fn prev(&self) -> &'a T {
+        cast(self.prev)
+    }
+
+    /// Get the current version
+    pub fn curr(&self) -> &'a T {
+        cast(self.curr)
+    }
+
+    /// Get the next version
+    pub fn next(&self) -> &'a T {
+        cast(self.next)
+    }
+}
+
+impl<'a, T> DiffObject<'a, T> {
+    /// Is this the object's first version?
+    pub fn first(&self) -> bool {
+        ptr::eq(self.prev, self.curr)
+    }
+
+    /// Is this the object's last version?
+    pub fn last(&self) -> bool {
+        ptr::eq(self.curr, self.next)
+    }
+
+    /// Get the object's id
+    pub fn id(&self) -> ObjectId {
+        self.curr.id()
+    }
+
+    /// Get the current version's number
+    pub fn version(&self) -> ObjectVersion {
+        self.curr.version()
+    }
+
+    /// Get the time from which on the current version is valid
+    pub fn start_time(&self) -> Timestamp {
+        self.curr.timestamp()
+    }
+
+    /// Get the time until which the current version is valid
+    ///
+    /// This is [`END_OF_TIME`] for the last version.
+    pub fn end_time(&self) -> Timestamp {
+        if self.last() {
+            END_OF_TIME
+        } else {
+            self.next.timestamp()
+        }
+    }
+
+    /// Is the current version valid and not deleted at a point in time?
+    pub fn is_visible_at(&self, timestamp: Timestamp) -> bool {
+        self.start_time() <= timestamp && timestamp < self.end_time() && self.curr.visible()
+    }
+
+    /// Is the current version valid and not deleted at some time between `from` and `to`?
+    pub fn is_between(&self, from: Timestamp, to: Timestamp) -> bool {
+        self.start_time() < to && from < self.end_time() && self.curr.visible()
+    }
+
+    /// Forget the object's concrete type
+    pub fn as_object(&self) -> DiffObject<'a, OSMObject> {
+        self.with_type()
+    }
+
+    /// Change the object's type without checking it
+    fn with_type<U>(&self) -> DiffObject<'a, U> {
+        DiffObject {
+            prev: self.prev,
+            curr: self.curr,
+            next: self.next,
+            subclass: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for DiffObject<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<T> Copy for DiffObject<'_, T> {}
+
+/// Cast an object into the type its [DiffObject] has been created with
+fn cast<T: ItemSubclass>(object: &OSMObject) -> &T {
+    let item = object.as_ref();
+    item.downcast()
+        .expect("A DiffObject's versions all have the same type")
+}
+
+/// A diff handler processes every version of the objects in a history file.
+///
+/// Like [Handler] every method does nothing by default.
+/// To read a file, wrap the handler in [Diffs] which implements [Handler]:
+///
+/// ```no_run
+/// use libosmium::diff::{DiffHandler, DiffObject, Diffs};
+/// use libosmium::handler::Handler;
+/// use libosmium::Node;
+///
+/// struct Moves;
+/// impl DiffHandler for Moves {
+///     fn node(&mut self, node: &DiffObject<Node>) {
+///         if !node.first() && node.prev().location() != node.curr().location() {
+///             println!("Node {} moved in version {}", node.id(), node.version());
+///         }
+///     }
+/// }
+///
+/// Diffs::new(Moves).apply("history.osh.pbf").unwrap();
+/// ```
+pub trait DiffHandler {
+    /// Process a version of a [Node]
+    fn node(&mut self, _node: &DiffObject<Node>) {}
+
+    /// Process a version of a [Way]
+    fn way(&mut self, _way: &DiffObject<Way>) {}
+
+    /// Process a version of a [Relation]
+    fn relation(&mut self, _relation: &DiffObject<Relation>) {}
+
+    /// Finalize temporary or lazy data
+    fn flush(&mut self) {}
+}
+
+impl<D: DiffHandler + ?Sized> DiffHandler for &mut D {
+    fn node(&mut self, node: &DiffObject<Node>) {
+        (**self).node(node);
+    }
+
+    fn way(&mut self, way: &DiffObject<Way>) {
+        (**self).way(way);
+    }
+
+    fn relation(&mut self, relation: &DiffObject<Relation>) {
+        (**self).relation(relation);
+    }
+
+    fn flush(&mut self) {
+        (**self).flush();
+    }
+}
+
+/// Adapter turning a [DiffHandler] into a [Handler]
+///
+/// Since libosmium reuses its buffers, the adapter copies the last three objects it has seen.
+/// Only nodes, ways and relations are processed, everything else (for example areas) is ignored.
+/// The input has to be sorted by type, id and version.
+#[derive(Clone, Debug, Default)]
+pub struct Diffs<D> {
+    handler: D,
+
+    /// Buffers holding a single object each: the previous, the current and the next one
+    window: [ItemBuffer; 3],
+}
+
+impl<D: DiffHandler> Diffs<D> {
+    /// Wrap a diff handler
+    pub fn new(handler: D) -> Self {
+        Self {
+            handler,
+            window: Default::default(),
+        }
+    }
+
+    /// Unwrap the diff handler
+    pub fn into_inner(self) -> D {
+        self.handler
+    }
+
+    /// Process a new object: Emit the current one, if any, and move the window forward
+    fn push(&mut self, object: &OSMObject) {
+        self.window[0].clear();
+        self.window[0].push(object);
+        self.window.rotate_left(1);
+        self.emit();
+    }
+
+    /// Emit the middle object of the window
+    fn emit(&mut self) {
+        let [prev, curr, next] = self
+            .window
+            .each_ref()
+            .map(|buffer| buffer.select::<OSMObject>().next());
+        let Some(curr) = curr else {
+            return;
+        };
+        let prev = prev
+            .filter(|prev| object_equal_type_id(prev, curr))
+            .unwrap_or(curr);
+        let next = next
+            .filter(|next| object_equal_type_id(next, curr))
+            .unwrap_or(curr);
+        let diff = DiffObject::<OSMObject> {
+            prev,
+            curr,
+            next,
+            subclass: PhantomData,
+        };
+
+        let item = curr.as_ref();
+        if item.downcast::<Node>().is_some() {
+            self.handler.node(&diff.with_type());
+        } else if item.downcast::<Way>().is_some() {
+            self.handler.way(&diff.with_type());
+        } else if item.downcast::<Relation>().is_some() {
+            self.handler.relation(&diff.with_type());
+        }
+    }
+}
+
+impl<D: DiffHandler> Handler for Diffs<D> {
+    fn node(&mut self, node: &Node) {
+        self.push(node);
+    }
+
+    fn way(&mut self, way: &Way) {
+        self.push(way);
+    }
+
+    fn relation(&mut self, relation: &Relation) {
+        self.push(relation);
+    }
+
+    fn flush(&mut self) {
+        // Emit the last object which has no next one
+        self.window[0].clear();
+        self.window.rotate_left(1);
+        self.emit();
+        self.window.iter_mut().for_each(ItemBuffer::clear);
+
+        self.handler.flush();
+    }
+}
+
+/// Create a [Handler] which reads a history file and passes only the versions valid at a point in time
+/// on to another handler, like `osmium time-filter` does.
+///
+/// Deleted objects are dropped, so the handler sees the data as it has been at `timestamp`.
+/// The input has to be sorted by type, id and version.
+///
+/// ```no_run
+/// use libosmium::diff::time_filter;
+/// use libosmium::handler::{from_fns, Handler};
+///
+/// // 2015-01-01T00:00:00Z
+/// let timestamp = 1420070400;
+/// let mut nodes = 0;
+/// time_filter(from_fns().node(|_node| nodes += 1), timestamp)
+///     .apply("history.osh.pbf")
+///     .unwrap();
+/// ```
+pub fn time_filter<H: Handler>(handler: H, timestamp: Timestamp) -> Diffs<TimeFilter<H>> {
+    Diffs::new(TimeFilter { handler, timestamp })
+}
+
+/// [DiffHandler] passing only the versions valid at a point in time on to a [Handler]
+///
+/// See [`time_filter`].
+#[derive(Clone, Debug, Default)]
+pub struct TimeFilter<H> {
+    handler: H,
+    timestamp: Timestamp,
+}
+
+impl<H> TimeFilter<H> {
+    /// Get the point in time the data is filtered for
+    pub fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+
+    /// Unwrap the handler
+    pub fn into_inner(self) -> H {
+        self.handler
+    }
+}
+
+impl<H: Handler> TimeFilter<H> {
+    /// Pass the current version on, if it is visible at the filter's timestamp
+    fn filter(&mut self, diff: DiffObject<'_>) {
+        if diff.is_visible_at(self.timestamp) {
+            self.handler.apply_item(diff.curr().as_ref());
+        }
+    }
+}
+
+impl<H: Handler> DiffHandler for TimeFilter<H> {
+    fn node(&mut self, node: &DiffObject<Node>) {
+        self.filter(node.as_object());
+    }
+
+    fn way(&mut self, way: &DiffObject<Way>) {
+        self.filter(way.as_object());
+    }
+
+    fn relation(&mut self, relation: &DiffObject<Relation>) {
+        self.filter(relation.as_object());
+    }
+
+    fn flush(&mut self) {
+        self.handler.flush();
+    }
+}
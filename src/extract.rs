@@ -0,0 +1,454 @@
+//! defines the [Extract] to cut the objects inside a region out of a file, like `osmium extract` does.
+//!
+//! The region is a [Boundary] which can be created from a [BBox](crate::BBox), an assembled [Area](crate::Area),
+//! a `.poly` file or GeoJSON.
+//! How objects crossing the region's border are handled depends on the [Strategy].
+//!
+//! ```no_run
+//! use libosmium::extract::{Extract, Strategy};
+//! use libosmium::handler::from_fns;
+//! use libosmium::io::File;
+//! use libosmium::BBox;
+//!
+//! let berlin = BBox::from_degrees(13.08, 52.33, 13.77, 52.68);
+//! let mut ways = 0;
+//! Extract::new(berlin)
+//!     .strategy(Strategy::Smart)
+//!     .apply(File::new("germany.osm.pbf"), &mut from_fns().way(|_way| ways += 1))?;
+//! # Ok::<(), libosmium::io::Error>(())
+//! ```
+//!
+//! The input has to be sorted by type and id, i.e. nodes before ways before relations,
+//! like planet dumps and extracts usually are.
+
+use std::collections::HashMap;
+
+use crate::boundary::Boundary;
+use crate::handler::Handler;
+use crate::id_set::{IdFilter, IdSet, IdSetDense, NwrArray};
+use crate::io::{self, File, Reader};
+use crate::item::ItemType;
+use crate::node::Node;
+use crate::object::UnsignedObjectId;
+use crate::relation::Relation;
+use crate::way::Way;
+
+/// How to handle objects crossing an extract's border
+///
+/// These mirror the strategies of `osmium extract`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Strategy {
+    /// Keep the nodes inside the region, the ways with at least one of those nodes
+    /// and the relations with at least one of those nodes or ways as member.
+    ///
+    /// Ways crossing the border are incomplete, because their nodes outside the region are missing.
+    /// This strategy reads the file once.
+    Simple,
+
+    /// Like [`Simple`](Strategy::Simple), but keep all nodes of the kept ways
+    /// and the relations having kept relations as members, recursively.
+    ///
+    /// This strategy reads the file twice.
+    #[default]
+    CompleteWays,
+
+    /// Like [`CompleteWays`](Strategy::CompleteWays), but additionally keep all member ways
+    /// (and their nodes) of the kept multipolygon relations, so they can be assembled into complete areas.
+    ///
+    /// This strategy reads the file three times.
+    Smart,
+}
+
+/// Extract of the objects inside a region
+///
+/// See the [module's documentation](self) for an example.
+#[derive(Clone, Debug)]
+pub struct Extract {
+    boundary: Boundary,
+    strategy: Strategy,
+}
+
+impl Extract {
+    /// Create an extract of a region using the [`CompleteWays`](Strategy::CompleteWays) strategy.
+    pub fn new(boundary: impl Into<Boundary>) -> Self {
+        Self {
+            boundary: boundary.into(),
+            strategy: Strategy::default(),
+        }
+    }
+
+    /// Set the strategy
+    pub fn strategy(mut self, strategy: Strategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Get the extract's region
+    pub fn boundary(&self) -> &Boundary {
+        &self.boundary
+    }
+
+    /// Read a file and pass the objects in the extract on to a handler.
+    ///
+    /// Strategies reading the file more than once read sources like stdin into memory entirely first.
+    /// Items other than nodes, ways and relations are dropped.
+    pub fn apply<H: Handler>(&self, file: File<'_>, handler: &mut H) -> Result<(), io::Error> {
+        if self.strategy == Strategy::Simple {
+            let mut simple = Simple {
+                boundary: &self.boundary,
+                ids: NwrArray::default(),
+                handler,
+            };
+            return Reader::new(file).apply(&mut simple);
+        }
+
+        let file = file.into_rereadable()?;
+        let mut collect = Collect {
+            boundary: &self.boundary,
+            ids: NwrArray::default(),
+            way_nodes: IdSetDense::new(),
+            parents: HashMap::new(),
+            multipolygon_ways: IdSetDense::new(),
+        };
+        Reader::new(file.reread()).apply(&mut collect)?;
+        let mut ids = collect.finish();
+
+        if self.strategy == Strategy::Smart && !collect.multipolygon_ways.is_empty() {
+            let mut complete = CompleteMultipolygons {
+                ways: &collect.multipolygon_ways,
+                ids: &mut ids,
+            };
+            Reader::new(file.reread()).apply(&mut complete)?;
+        }
+
+        Reader::new(file.reread()).apply(&mut IdFilter::new(&ids, NwrOnly(handler)))
+    }
+}
+
+/// Handler implementing [`Strategy::Simple`] in a single pass
+struct Simple<'e, H> {
+    boundary: &'e Boundary,
+    ids: NwrArray<IdSetDense>,
+    handler: H,
+}
+
+impl<H: Handler> Handler for Simple<'_, H> {
+    fn node(&mut self, node: &Node) {
+        if self.boundary.contains(node.location()) {
+            self.ids[ItemType::Node].set(node.positive_id());
+            self.handler.apply_item(node.as_ref());
+        }
+    }
+
+    fn way(&mut self, way: &Way) {
+        if has_node_in(way, &self.ids[ItemType::Node]) {
+            self.ids[ItemType::Way].set(way.positive_id());
+            self.handler.apply_item(way.as_ref());
+        }
+    }
+
+    fn relation(&mut self, relation: &Relation) {
+        let kept = relation.members().into_iter().any(|member| {
+            matches!(member.member_type(), ItemType::Node | ItemType::Way)
+                && self.ids[member.member_type()].get(member.member_ref().unsigned_abs())
+        });
+        if kept {
+            self.handler.apply_item(relation.as_ref());
+        }
+    }
+
+    fn flush(&mut self) {
+        self.handler.flush();
+    }
+}
+
+/// Handler for the first pass of [`Strategy::CompleteWays`] and [`Strategy::Smart`] collecting the ids to keep
+struct Collect<'e> {
+    boundary: &'e Boundary,
+
+    /// The ids of the nodes inside the region and of the kept ways and relations
+    ids: NwrArray<IdSetDense>,
+
+    /// The nodes of the kept ways
+    ///
+    /// They are kept apart from the nodes inside the region until the first pass is finished,
+    /// so they don't cause further ways to be kept.
+    way_nodes: IdSetDense,
+
+    /// The relations which have a relation as member by the member's id
+    parents: HashMap<UnsignedObjectId, Vec<UnsignedObjectId>>,
+
+    /// The member ways of the kept multipolygon relations
+    multipolygon_ways: IdSetDense,
+}
+
+impl Collect<'_> {
+    /// Add the parents of the kept relations recursively and return the ids to keep
+    fn finish(&mut self) -> NwrArray<IdSetDense> {
+        let mut queue: Vec<UnsignedObjectId> = self.ids[ItemType::Relation].iter().collect();
+        while let Some(id) = queue.pop() {
+            for parent in self.parents.remove(&id).into_iter().flatten() {
+                if self.ids[ItemType::Relation].check_and_set(parent) {
+                    queue.push(parent);
+                }
+            }
+        }
+
+        let mut ids = std::mem::take(&mut self.ids);
+        for id in self.way_nodes.iter() {
+            ids[ItemType::Node].set(id);
+        }
+        ids
+    }
+}
+
+impl Handler for Collect<'_> {
+    fn node(&mut self, node: &Node) {
+        if self.boundary.contains(node.location()) {
+            self.ids[ItemType::Node].set(node.positive_id());
+        }
+    }
+
+    fn way(&mut self, way: &Way) {
+        if has_node_in(way, &self.ids[ItemType::Node]) {
+            self.ids[ItemType::Way].set(way.positive_id());
+            for node in way.nodes().iter() {
+                self.way_nodes.set(node.id.unsigned_abs());
+            }
+        }
+    }
+
+    fn relation(&mut self, relation: &Relation) {
+        let id = relation.positive_id();
+        let mut kept = false;
+        for member in relation.members() {
+            let member_id = member.member_ref().unsigned_abs();
+            match member.member_type() {
+                ItemType::Node | ItemType::Way => {
+                    kept |= self.ids[member.member_type()].get(member_id);
+                }
+                ItemType::Relation => self.parents.entry(member_id).or_default().push(id),
+                _ => {}
+            }
+        }
+        if !kept {
+            return;
+        }
+
+        self.ids[ItemType::Relation].set(id);
+        if relation.tags().get("type") == Some("multipolygon") {
+            for member in relation.members() {
+                if member.member_type() == ItemType::Way {
+                    self.multipolygon_ways
+                        .set(member.member_ref().unsigned_abs());
+                }
+            }
+        }
+    }
+}
+
+/// Handler for the second pass of [`Strategy::Smart`] adding the multipolygons' ways and their nodes
+struct CompleteMultipolygons<'e> {
+    ways: &'e IdSetDense,
+    ids: &'e mut NwrArray<IdSetDense>,
+}
+
+impl Handler for CompleteMultipolygons<'_> {
+    fn way(&mut self, way: &Way) {
+        if self.ways.get(way.positive_id()) {
+            self.ids[ItemType::Way].set(way.positive_id());
+            self.ids.add_way_nodes(way.nodes());
+        }
+    }
+}
+
+/// Adapter passing only nodes, ways and relations on to a handler
+struct NwrOnly<H>(H);
+
+impl<H: Handler> Handler for NwrOnly<H> {
+    fn node(&mut self, node: &Node) {
+        self.0.apply_item(node.as_ref());
+    }
+
+    fn way(&mut self, way: &Way) {
+        self.0.apply_item(way.as_ref());
+    }
+
+    fn relation(&mut self, relation: &Relation) {
+        self.0.apply_item(relation.as_ref());
+    }
+
+    fn flush(&mut self) {
+        self.0.flush();
+    }
+}
+
+/// Check whether any of a way's nodes is in a set
+fn has_node_in(way: &Way, nodes: &IdSetDense) -> bool {
+    way.nodes()
+        .iter()
+        .any(|node| nodes.get(node.id.unsigned_abs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::tests::{located_node, relation, way};
+    use crate::buffer::ItemBuffer;
+    use crate::location::{BBox, Location};
+
+    /// The test data around a region from 0 to 10 degrees
+    ///
+    /// - Nodes 1 and 5 are inside, nodes 2, 3 and 4 outside.
+    /// - Way 10 crosses the border, way 11 shares only the outside node 2 with it and
+    ///   way 12 is outside, but a member of the multipolygon 21.
+    /// - Relation 20 has the node 5 as member, 21 the ways 10 and 12,
+    ///   22 the relation 20 and 23 only the way 11.
+    fn data() -> ItemBuffer {
+        let mut bytes = Vec::new();
+        for (id, lon, lat) in [
+            (1, 5.0, 5.0),
+            (2, 20.0, 20.0),
+            (3, 21.0, 21.0),
+            (4, 22.0, 22.0),
+            (5, 5.0, 6.0),
+        ] {
+            bytes.extend(located_node(id, Location::new(lon, lat)));
+        }
+        bytes.extend(way(10, &[1, 2]));
+        bytes.extend(way(11, &[2, 3]));
+        bytes.extend(way(12, &[3, 4, 3]));
+        bytes.extend(relation(20, b"", &[(ItemType::Node, 5)]));
+        bytes.extend(relation(
+            21,
+            b"type\0multipolygon\0",
+            &[(ItemType::Way, 10), (ItemType::Way, 12)],
+        ));
+        bytes.extend(relation(22, b"", &[(ItemType::Relation, 20)]));
+        bytes.extend(relation(23, b"", &[(ItemType::Way, 11)]));
+        ItemBuffer::from_bytes(&bytes).unwrap()
+    }
+
+    fn boundary() -> Boundary {
+        Boundary::from(BBox::from_degrees(0.0, 0.0, 10.0, 10.0))
+    }
+
+    /// Get the sorted ids of a set
+    fn ids(set: &IdSetDense) -> Vec<UnsignedObjectId> {
+        set.iter().collect()
+    }
+
+    /// Handler recording the ids of the objects passed on to it
+    #[derive(Default)]
+    struct Kept(NwrArray<IdSetDense>);
+
+    impl Handler for Kept {
+        fn node(&mut self, node: &Node) {
+            self.0[ItemType::Node].set(node.positive_id());
+        }
+
+        fn way(&mut self, way: &Way) {
+            self.0[ItemType::Way].set(way.positive_id());
+        }
+
+        fn relation(&mut self, relation: &Relation) {
+            self.0[ItemType::Relation].set(relation.positive_id());
+        }
+    }
+
+    /// Run the first pass of [`Strategy::CompleteWays`] and [`Strategy::Smart`] over the test data
+    fn collect(boundary: &Boundary) -> Collect<'_> {
+        let mut collect = Collect {
+            boundary,
+            ids: NwrArray::default(),
+            way_nodes: IdSetDense::new(),
+            parents: HashMap::new(),
+            multipolygon_ways: IdSetDense::new(),
+        };
+        for item in data().iter() {
+            collect.apply_item(item);
+        }
+        collect
+    }
+
+    #[test]
+    fn simple() {
+        let boundary = boundary();
+        let mut kept = Kept::default();
+        let mut simple = Simple {
+            boundary: &boundary,
+            ids: NwrArray::default(),
+            handler: &mut kept,
+        };
+        for item in data().iter() {
+            simple.apply_item(item);
+        }
+
+        assert_eq!(ids(&kept.0[ItemType::Node]), [1, 5]);
+        assert_eq!(ids(&kept.0[ItemType::Way]), [10]);
+        assert_eq!(ids(&kept.0[ItemType::Relation]), [20, 21]);
+    }
+
+    #[test]
+    fn complete_ways() {
+        let boundary = boundary();
+        let mut collect = collect(&boundary);
+        assert_eq!(ids(&collect.multipolygon_ways), [10, 12]);
+
+        // The nodes of way 10 don't cause way 11 to be kept
+        let ids_to_keep = collect.finish();
+        assert_eq!(ids(&ids_to_keep[ItemType::Node]), [1, 2, 5]);
+        assert_eq!(ids(&ids_to_keep[ItemType::Way]), [10]);
+        assert_eq!(ids(&ids_to_keep[ItemType::Relation]), [20, 21, 22]);
+    }
+
+    #[test]
+    fn smart() {
+        let boundary = boundary();
+        let mut collect = collect(&boundary);
+        let mut ids_to_keep = collect.finish();
+        let mut complete = CompleteMultipolygons {
+            ways: &collect.multipolygon_ways,
+            ids: &mut ids_to_keep,
+        };
+        for item in data().iter() {
+            complete.apply_item(item);
+        }
+
+        assert_eq!(ids(&ids_to_keep[ItemType::Node]), [1, 2, 3, 4, 5]);
+        assert_eq!(ids(&ids_to_keep[ItemType::Way]), [10, 12]);
+        assert_eq!(ids(&ids_to_keep[ItemType::Relation]), [20, 21, 22]);
+    }
+
+    #[test]
+    fn finish_propagates_to_parents() {
+        let boundary = Boundary::default();
+        let mut collect = Collect {
+            boundary: &boundary,
+            ids: NwrArray::default(),
+            way_nodes: IdSetDense::new(),
+            parents: HashMap::new(),
+            multipolygon_ways: IdSetDense::new(),
+        };
+        collect.ids[ItemType::Node].set(12);
+        collect.ids[ItemType::Way].set(20);
+        collect.ids[ItemType::Relation].set(1);
+        collect.way_nodes.set(10);
+        collect.way_nodes.set(11);
+
+        // 1 is a member of 2, which is a member of 3, which is a member of 1 again,
+        // while 4 isn't kept and neither is its parent 5
+        collect.parents.insert(1, vec![2]);
+        collect.parents.insert(2, vec![3]);
+        collect.parents.insert(3, vec![1]);
+        collect.parents.insert(4, vec![5]);
+
+        let ids = collect.finish();
+        assert_eq!(ids[ItemType::Node].iter().collect::<Vec<_>>(), [10, 11, 12]);
+        assert_eq!(ids[ItemType::Way].iter().collect::<Vec<_>>(), [20]);
+        assert_eq!(
+            ids[ItemType::Relation].iter().collect::<Vec<_>>(),
+            [1, 2, 3]
+        );
+    }
+}
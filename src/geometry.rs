@@ -0,0 +1,237 @@
+//! Helpers for planar calculations on rings which are shared by the rings, areas and boxes.
+//!
+//! Tests on points and segments use the raw integer coordinates to be exact.
+
+use crate::location::{BBox, Location};
+use crate::node::NodeRef;
+
+/// Iterate over the valid locations of a list of nodes
+pub(crate) fn locations(nodes: &[NodeRef]) -> impl Iterator<Item = Location> + '_ {
+    nodes.iter().filter_map(NodeRef::get_location)
+}
+
+/// Iterate over a ring's edges, connecting the last location to the first one
+pub(crate) fn edges(
+    locations: impl IntoIterator<Item = Location>,
+) -> impl Iterator<Item = (Location, Location)> {
+    let mut locations = locations.into_iter();
+    let first = locations.next();
+    let mut previous = first;
+    locations
+        .chain(first)
+        .filter_map(move |location| Some((previous.replace(location)?, location)))
+}
+
+/// Calculate a ring's signed area in square degrees using the shoelace formula
+pub(crate) fn signed_area(ring: impl IntoIterator<Item = Location>) -> f64 {
+    let twice_area: f64 = edges(ring)
+        .map(|(a, b)| a.lon() * b.lat() - b.lon() * a.lat())
+        .sum();
+    twice_area / 2.0
+}
+
+/// Check whether a location is inside a ring using the crossing number algorithm
+pub(crate) fn ring_contains(ring: impl IntoIterator<Item = Location>, location: Location) -> bool {
+    let (x, y) = (location.raw_x as i128, location.raw_y as i128);
+    let mut inside = false;
+    for (a, b) in edges(ring) {
+        let (ax, ay) = (a.raw_x as i128, a.raw_y as i128);
+        let (bx, by) = (b.raw_x as i128, b.raw_y as i128);
+        if (ay > y) != (by > y) {
+            // Sign of the location relative to the edge, flipped for downwards edges
+            let side = (bx - ax) * (y - ay) - (x - ax) * (by - ay);
+            if (side > 0) == (by > ay) {
+                inside = !inside;
+            }
+        }
+    }
+    inside
+}
+
+/// Check whether a location is inside a polygon's outer ring, but none of its inner rings
+pub(crate) fn polygon_contains<R: IntoIterator<Item = Location>>(
+    outer: R,
+    inner: impl IntoIterator<Item = R>,
+    location: Location,
+) -> bool {
+    ring_contains(outer, location)
+        && !inner
+            .into_iter()
+            .any(|inner| ring_contains(inner, location))
+}
+
+/// Check whether any of a polygon's rings touches a box, including its border
+pub(crate) fn polygon_rings_intersect_bbox<R: IntoIterator<Item = Location>>(
+    outer: R,
+    inner: impl IntoIterator<Item = R>,
+    bbox: &BBox,
+) -> bool {
+    std::iter::once(outer)
+        .chain(inner)
+        .any(|ring| ring_intersects_bbox(ring, bbox))
+}
+
+/// Check whether any of a ring's edges touches a box, including its border
+pub(crate) fn ring_intersects_bbox(ring: impl IntoIterator<Item = Location>, bbox: &BBox) -> bool {
+    edges(ring).any(|(a, b)| segment_intersects_bbox(a, b, bbox))
+}
+
+/// Check whether a segment touches a box, including its border
+pub(crate) fn segment_intersects_bbox(a: Location, b: Location, bbox: &BBox) -> bool {
+    if bbox.contains(a) || bbox.contains(b) {
+        return true;
+    }
+    let BBox {
+        bottom_left,
+        top_right,
+    } = *bbox;
+    let bottom_right = Location {
+        raw_x: top_right.raw_x,
+        raw_y: bottom_left.raw_y,
+    };
+    let top_left = Location {
+        raw_x: bottom_left.raw_x,
+        raw_y: top_right.raw_y,
+    };
+    segments_intersect(a, b, bottom_left, bottom_right)
+        || segments_intersect(a, b, bottom_right, top_right)
+        || segments_intersect(a, b, top_right, top_left)
+        || segments_intersect(a, b, top_left, bottom_left)
+}
+
+/// Check whether two segments share at least one point
+pub(crate) fn segments_intersect(a: Location, b: Location, c: Location, d: Location) -> bool {
+    let (abc, abd) = (orientation(a, b, c), orientation(a, b, d));
+    let (cda, cdb) = (orientation(c, d, a), orientation(c, d, b));
+    if abc * abd < 0 && cda * cdb < 0 {
+        return true;
+    }
+    (abc == 0 && on_segment(a, b, c))
+        || (abd == 0 && on_segment(a, b, d))
+        || (cda == 0 && on_segment(c, d, a))
+        || (cdb == 0 && on_segment(c, d, b))
+}
+
+/// Is `c` left of (1), right of (-1) or on (0) the line through `a` and `b`?
+fn orientation(a: Location, b: Location, c: Location) -> i8 {
+    let cross = (b.raw_x as i128 - a.raw_x as i128) * (c.raw_y as i128 - a.raw_y as i128)
+        - (b.raw_y as i128 - a.raw_y as i128) * (c.raw_x as i128 - a.raw_x as i128);
+    cross.signum() as i8
+}
+
+/// Is `c`, which is on the line through `a` and `b`, between them?
+fn on_segment(a: Location, b: Location, c: Location) -> bool {
+    a.raw_x.min(b.raw_x) <= c.raw_x
+        && c.raw_x <= a.raw_x.max(b.raw_x)
+        && a.raw_y.min(b.raw_y) <= c.raw_y
+        && c.raw_y <= a.raw_y.max(b.raw_y)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Square ring from its corners' longitudes and latitudes in degrees
+    fn square(min: f64, max: f64) -> Vec<Location> {
+        vec![
+            Location::new(min, min),
+            Location::new(max, min),
+            Location::new(max, max),
+            Location::new(min, max),
+        ]
+    }
+
+    /// Ten degrees square with a hole of four degrees in its middle
+    fn polygon_with_hole() -> (Vec<Location>, Vec<Vec<Location>>) {
+        (square(0.0, 10.0), vec![square(3.0, 7.0)])
+    }
+
+    fn contains(location: Location) -> bool {
+        let (outer, inner) = polygon_with_hole();
+        polygon_contains(outer, inner, location)
+    }
+
+    fn rings_intersect(bbox: &BBox) -> bool {
+        let (outer, inner) = polygon_with_hole();
+        polygon_rings_intersect_bbox(outer, inner, bbox)
+    }
+
+    #[test]
+    fn ring_with_hole() {
+        assert!(contains(Location::new(1.0, 1.0)));
+        assert!(contains(Location::new(8.0, 5.0)));
+        assert!(!contains(Location::new(5.0, 5.0)));
+        assert!(!contains(Location::new(11.0, 5.0)));
+        assert!(!contains(Location::new(-1.0, -1.0)));
+
+        assert!(ring_contains(square(3.0, 7.0), Location::new(5.0, 5.0)));
+        assert_eq!(signed_area(square(0.0, 10.0)), 100.0);
+        assert_eq!(signed_area(square(0.0, 10.0).into_iter().rev()), -100.0);
+    }
+
+    #[test]
+    fn bbox_inside_hole() {
+        // Neither touches a ring nor has a corner inside, so the polygon and the box are disjoint
+        let bbox = BBox::from_degrees(4.0, 4.0, 6.0, 6.0);
+        assert!(!rings_intersect(&bbox));
+        assert!(!contains(bbox.bottom_left));
+
+        // Around the hole, so the box intersects the polygon without being inside it
+        let bbox = BBox::from_degrees(2.0, 2.0, 8.0, 8.0);
+        assert!(rings_intersect(&bbox));
+        assert!(contains(bbox.bottom_left));
+
+        // Between the outer ring and the hole, so the box is inside the polygon
+        let bbox = BBox::from_degrees(1.0, 1.0, 2.0, 9.0);
+        assert!(!rings_intersect(&bbox));
+        assert!(contains(bbox.bottom_left));
+    }
+
+    #[test]
+    fn bbox_touching_rings() {
+        assert!(rings_intersect(&BBox::from_degrees(6.0, 6.0, 8.0, 8.0)));
+        assert!(rings_intersect(&BBox::from_degrees(7.0, 4.0, 8.0, 6.0)));
+        assert!(rings_intersect(&BBox::from_degrees(-1.0, -1.0, 11.0, 11.0)));
+        assert!(rings_intersect(&BBox::from_degrees(10.0, 10.0, 11.0, 11.0)));
+        assert!(!rings_intersect(&BBox::from_degrees(1.0, 1.0, 2.0, 2.0)));
+        assert!(!rings_intersect(&BBox::from_degrees(
+            11.0, 11.0, 12.0, 12.0
+        )));
+    }
+
+    #[test]
+    fn crossing_segments() {
+        let location = |lon, lat| Location::new(lon, lat);
+        let (a, b) = (location(0.0, 0.0), location(2.0, 2.0));
+        assert!(segments_intersect(
+            a,
+            b,
+            location(0.0, 2.0),
+            location(2.0, 0.0)
+        ));
+        assert!(segments_intersect(
+            a,
+            b,
+            location(1.0, 1.0),
+            location(3.0, 0.0)
+        ));
+        assert!(segments_intersect(
+            a,
+            b,
+            location(2.0, 2.0),
+            location(3.0, 3.0)
+        ));
+        assert!(!segments_intersect(
+            a,
+            b,
+            location(3.0, 3.0),
+            location(4.0, 4.0)
+        ));
+        assert!(!segments_intersect(
+            a,
+            b,
+            location(0.0, 1.0),
+            location(1.0, 2.0)
+        ));
+    }
+}
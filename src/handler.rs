@@ -6,12 +6,17 @@
 use std::ffi::{c_void, CStr, CString};
 use std::marker::PhantomData;
 use std::os::raw::{c_char, c_int};
+use std::path::Path;
 use std::ptr;
 
 use crate::area::Area;
+use crate::buffer::ItemBuffer;
+use crate::io::{self, File, Files, Reader};
+use crate::item::{Item, ItemRef};
 use crate::node::Node;
 use crate::node_ref_list::{InnerRing, OuterRing, WayNodeList};
 use crate::object::OSMObject;
+pub use crate::relation::{Relation, RelationMemberList};
 use crate::tag_list::TagList;
 use crate::way::Way;
 
@@ -19,46 +24,44 @@ use crate::way::Way;
 pub enum Changeset {}
 /// Not implemented yet
 pub enum ChangesetDiscussion {}
-/// Not implemented yet
-pub enum Relation {}
-/// Not implemented yet
-pub enum RelationMemberList {}
 
 extern "C" {
     /// error_buffer is expected to be 256 bytes in size
     /// and c++ will only write 255, leaving the last one NUL.
-    fn apply(handler: HandlerTable, file: *const c_char, error_buffer: *mut c_char);
-    fn apply_with_ways(handler: HandlerTable, file: *const c_char, error_buffer: *mut c_char);
-    fn apply_with_areas(
+    pub(crate) fn apply(handler: HandlerTable, files: &Files, error_buffer: *mut c_char);
+    pub(crate) fn apply_with_ways(handler: HandlerTable, files: &Files, error_buffer: *mut c_char);
+    pub(crate) fn apply_relations(handler: HandlerTable, files: &Files, error_buffer: *mut c_char);
+    pub(crate) fn apply_with_areas(
         handler: HandlerTable,
-        file: *const c_char,
+        files: &Files,
+        error_buffer: *mut c_char,
+        config: AreaAssemblerConfig,
+    );
+    pub(crate) fn apply_with_way_areas(
+        handler: HandlerTable,
+        files: &Files,
         error_buffer: *mut c_char,
         config: AreaAssemblerConfig,
     );
 }
 
-/// Macro to wrap ffi's apply functions
-macro_rules! impl_apply {
-    ($function:ident, $handler:expr, $file:expr) => {
-        impl_apply!($function, $handler, $file, )
-    };
-    ($function:ident, $handler:expr, $file:expr, $($args:expr),*) => {{
-        let file = CString::new($file).expect("File can't contain a NUL character");
-
-        let mut error: [c_char; 256] = [0; 256];
-        unsafe { $function($handler.as_table(), file.as_ptr(), error.as_mut_ptr(), $($args),*) };
-        let error = error;
-
-        // Empty error message -> no error
-        if error[0] == 0 {
-            Ok(())
-        } else {
-            // Safe, because error is existing memory on stack
-            // and c++ only writes to 255 of the 256 bytes, so the last one will always stay a NUL.
-            let cstr = unsafe { CStr::from_ptr(error.as_ptr()) };
-            Err(CString::from(cstr))
-        }
-    }};
+/// Call an ffi function which reports exceptions by writing their message into an error buffer
+///
+/// The function is passed a pointer to a 256 bytes large buffer.
+pub(crate) fn catch_error(function: impl FnOnce(*mut c_char)) -> Result<(), CString> {
+    let mut error: [c_char; 256] = [0; 256];
+    function(error.as_mut_ptr());
+    let error = error;
+
+    // Empty error message -> no error
+    if error[0] == 0 {
+        Ok(())
+    } else {
+        // Safe, because error is existing memory on stack
+        // and c++ only writes to 255 of the 256 bytes, so the last one will always stay a NUL.
+        let cstr = unsafe { CStr::from_ptr(error.as_ptr()) };
+        Err(CString::from(cstr))
+    }
 }
 
 /// A handler is the entry interface for processing OSM files.
@@ -71,8 +74,8 @@ macro_rules! impl_apply {
 /// The trait also has a few other methods. [`flush`](Handler::flush) will be called at least once,
 /// after the final item has been processed to finalize a potentially lazy process.
 ///
-/// The methods [`as_table`](Handler::as_table) and [`apply...`](Handler::apply) are not intended to be overwritten.
-/// Instead they implement reading and processing PBF files using the handler instance.
+/// The methods [`apply_item`](Handler::apply_item), [`as_table`](Handler::as_table) and [`apply...`](Handler::apply) are not intended to be overwritten.
+/// Instead they implement reading and processing osm files using the handler instance.
 ///
 /// This trait roughly mimics [`osmium::handler::Handler`](https://osmcode.org/libosmium/manual.html#handlers)
 /// and can be converted into the subclass `RustHandler` (see `src/libosmium.cpp`) using [`as_table`](Handler::as_table).
@@ -116,6 +119,52 @@ pub trait Handler {
     /// Finalize temporary ore lazy data
     fn flush(&mut self) {}
 
+    /// Get notified about the progress made reading the file
+    ///
+    /// This is called after every buffer of items has been processed.
+    fn progress(&mut self, _progress: &Progress) {}
+
+    /// Process a single item by calling the methods associated with its type.
+    ///
+    /// Like [`osmium::apply_item`](https://docs.osmcode.org/libosmium/latest/namespaceosmium.html),
+    /// this calls [`osm_object`](Handler::osm_object) before the specific method for nodes, ways, relations and areas.
+    fn apply_item(&mut self, item: &Item) {
+        match item.cast() {
+            Some(ItemRef::Node(node)) => {
+                self.osm_object(node);
+                self.node(node);
+            }
+            Some(ItemRef::Way(way)) => {
+                self.osm_object(way);
+                self.way(way);
+            }
+            Some(ItemRef::Relation(relation)) => {
+                self.osm_object(relation);
+                self.relation(relation);
+            }
+            Some(ItemRef::Area(area)) => {
+                self.osm_object(area);
+                self.area(area);
+            }
+            Some(ItemRef::Changeset(changeset)) => self.changeset(changeset),
+            Some(ItemRef::TagList(tag_list)) => self.tag_list(tag_list),
+            Some(ItemRef::WayNodeList(list)) => self.way_node_list(list),
+            Some(ItemRef::RelationMemberList(list)) => self.relation_member_list(list),
+            Some(ItemRef::OuterRing(ring)) => self.outer_ring(ring),
+            Some(ItemRef::InnerRing(ring)) => self.inner_ring(ring),
+            Some(ItemRef::ChangesetDiscussion(discussion)) => self.changeset_discussion(discussion),
+            None => {}
+        }
+    }
+
+    /// Process all items in a buffer using this handler and [`flush`](Handler::flush) afterwards.
+    fn apply_buffer(&mut self, buffer: &ItemBuffer) {
+        for item in buffer {
+            self.apply_item(item);
+        }
+        self.flush();
+    }
+
     /// Convert the handler into a [HandlerTable] which the c++ shim can interpret as a `RustHandler`
     fn as_table(&mut self) -> HandlerTable {
         HandlerTable {
@@ -134,26 +183,36 @@ pub trait Handler {
             way: Self::way as *const (),
             way_node_list: Self::way_node_list as *const (),
             flush: Self::flush as *const (),
+            progress: Self::progress as *const (),
         }
     }
 
-    /// Read a PBF file and process its items using this handler without any preprocessing.
+    /// Read a file and process its items using this handler without any preprocessing.
     ///
     /// If you'd like c++ to do some preprocessing you might consider:
     /// - [`apply_with_ways`](Handler::apply_with_ways) populates a way's nodes' locations.
     /// - [`apply_with_areas`](Handler::apply_with_areas) assembles areas from ways and relations.
-    fn apply(&mut self, file: &str) -> Result<(), CString> {
-        impl_apply!(apply, self, file)
+    /// - [`apply_with_way_areas`](Handler::apply_with_way_areas) assembles areas from closed ways only.
+    ///
+    /// To read from other sources than a path, use a [`Reader`].
+    fn apply(&mut self, file: impl AsRef<Path>) -> Result<(), io::Error>
+    where
+        Self: Sized,
+    {
+        Reader::new(File::new(file)).apply(self)
     }
 
-    /// Read a PBF file, populates a way's nodes' locations and process the items using this handler.
+    /// Read a file, populates a way's nodes' locations and process the items using this handler.
     ///
     /// The preprocessing step of populating ways works by storing all already seen nodes' locations
     /// in a map and copying them into a way's node refs.
-    fn apply_with_ways(&mut self, file: &str) -> Result<(), CString> {
-        impl_apply!(apply_with_ways, self, file)
+    fn apply_with_ways(&mut self, file: impl AsRef<Path>) -> Result<(), io::Error>
+    where
+        Self: Sized,
+    {
+        Reader::new(File::new(file)).apply_with_ways(self)
     }
-    /// Read a PBF file, assemble areas and process the items using this handler.
+    /// Read a file, assemble areas and process the items using this handler.
     ///
     /// Since areas are [not actual osm items](Area#way-or-relation) stored in the file, you need to you this method,
     /// when you need areas at all. (Unless you write your own preprocessor.)
@@ -162,9 +221,178 @@ pub trait Handler {
     ///
     /// Assembling areas is actually more involved than populating ways
     /// and requires an additional pass through the entire file increasing time and memory cost.
-    fn apply_with_areas(&mut self, file: &str, config: AreaAssemblerConfig) -> Result<(), CString> {
-        impl_apply!(apply_with_areas, self, file, config)
+    fn apply_with_areas(
+        &mut self,
+        file: impl AsRef<Path>,
+        config: AreaAssemblerConfig,
+    ) -> Result<(), io::Error>
+    where
+        Self: Sized,
+    {
+        Reader::new(File::new(file)).apply_with_areas(self, config)
     }
+
+    /// Read a file, assemble areas from closed ways only and process the items using this handler.
+    ///
+    /// Unlike [`apply_with_areas`](Handler::apply_with_areas) multipolygon relations are ignored,
+    /// which saves the additional pass through the file.
+    /// This is enough when only simple polygons like building footprints are of interest.
+    ///
+    /// Like for the ways not in any multipolygon in [`apply_with_areas`](Handler::apply_with_areas),
+    /// untagged ways, ways tagged `area=no` and ways with invalid locations are skipped.
+    /// The relations themselves are still passed to the handler.
+    /// Each area is processed directly after the way it has been assembled from.
+    fn apply_with_way_areas(
+        &mut self,
+        file: impl AsRef<Path>,
+        config: AreaAssemblerConfig,
+    ) -> Result<(), io::Error>
+    where
+        Self: Sized,
+    {
+        Reader::new(File::new(file)).apply_with_way_areas(self, config)
+    }
+}
+
+/// Macro implementing [Handler]'s processing methods by calling them on each of the handlers in `$handlers`
+macro_rules! impl_delegation {
+    ($self:ident, $($handler:ident in $handlers:expr),+) => {
+        impl_delegation!(@methods $self, [$($handler in $handlers),+],
+            area(area: &Area),
+            changeset(changeset: &Changeset),
+            changeset_discussion(changeset_discussion: &ChangesetDiscussion),
+            inner_ring(inner_ring: &InnerRing),
+            node(node: &Node),
+            osm_object(object: &OSMObject),
+            outer_ring(outer_ring: &OuterRing),
+            relation(relation: &Relation),
+            relation_member_list(relation_member_list: &RelationMemberList),
+            tag_list(tag_list: &TagList),
+            way(way: &Way),
+            way_node_list(way_node_list: &WayNodeList),
+            flush(),
+            progress(progress: &Progress),
+        );
+    };
+    (@methods $self:ident, $handlers:tt, $($method:ident($($arg:ident: $type:ty)?),)*) => {
+        $(
+            fn $method(&mut $self, $($arg: $type)?) {
+                impl_delegation!(@call $handlers, $method($($arg)?));
+            }
+        )*
+    };
+    (@call [$handler:ident in $handlers:expr $(, $rest:ident in $rests:expr)*], $method:ident $args:tt) => {
+        for $handler in $handlers {
+            $handler.$method $args;
+        }
+        impl_delegation!(@call [$($rest in $rests),*], $method $args);
+    };
+    (@call [], $method:ident $args:tt) => {};
+}
+
+/// Forward a mutable reference's methods to the referenced handler
+///
+/// This allows passing borrowed handlers to combinators like tuples or a [Vec].
+impl<H: Handler + ?Sized> Handler for &mut H {
+    impl_delegation!(self, handler in [&mut **self]);
+}
+
+/// Run several handlers of the same type (for example `&mut dyn Handler`) in one pass.
+///
+/// Each item is passed to all handlers in order, before continuing with the next item.
+impl<H: Handler> Handler for Vec<H> {
+    impl_delegation!(self, handler in self.iter_mut());
+}
+
+/// Macro implementing [Handler] for tuples of handlers
+macro_rules! impl_tuple {
+    ($($name:ident.$index:tt),+) => {
+        /// Run several handlers in one pass.
+        ///
+        /// Each item is passed to all handlers in order, before continuing with the next item.
+        impl<$($name: Handler),+> Handler for ($($name,)+) {
+            impl_delegation!(self, $(handler in [&mut self.$index]),+);
+        }
+    };
+}
+impl_tuple!(A.0);
+impl_tuple!(A.0, B.1);
+impl_tuple!(A.0, B.1, C.2);
+impl_tuple!(A.0, B.1, C.2, D.3);
+impl_tuple!(A.0, B.1, C.2, D.3, E.4);
+impl_tuple!(A.0, B.1, C.2, D.3, E.4, F.5);
+impl_tuple!(A.0, B.1, C.2, D.3, E.4, F.5, G.6);
+impl_tuple!(A.0, B.1, C.2, D.3, E.4, F.5, G.6, H.7);
+
+/// Start building a [Handler] from closures.
+///
+/// Only the methods a closure is provided for do something, everything else is ignored.
+///
+/// ```no_run
+/// use libosmium::handler::{from_fns, Handler};
+///
+/// let mut nodes = 0;
+/// from_fns()
+///     .node(|_node| nodes += 1)
+///     .apply("planet.osm.pbf")
+///     .unwrap();
+/// println!("The planet has {nodes} nodes");
+/// ```
+///
+/// The closures may borrow local state mutably.
+/// To share mutable state between multiple closures, wrap it in a [`Cell`](std::cell::Cell)
+/// or [`RefCell`](std::cell::RefCell).
+pub fn from_fns<'a>() -> FnHandler<'a> {
+    FnHandler::default()
+}
+
+/// Macro to define [FnHandler] and its builder methods
+macro_rules! fn_handler {
+    ($($method:ident($($arg:ident: $type:ty)?),)*) => {
+        /// A [Handler] built from closures using [`from_fns`].
+        #[derive(Default)]
+        pub struct FnHandler<'a> {
+            $(
+                $method: Option<Box<dyn FnMut($($type)?) + 'a>>,
+            )*
+        }
+
+        impl<'a> FnHandler<'a> {
+            $(
+                #[doc = concat!("Use a closure as [`", stringify!($method), "`](Handler::", stringify!($method), ")")]
+                pub fn $method(mut self, function: impl FnMut($($type)?) + 'a) -> Self {
+                    self.$method = Some(Box::new(function));
+                    self
+                }
+            )*
+        }
+
+        impl Handler for FnHandler<'_> {
+            $(
+                fn $method(&mut self, $($arg: $type)?) {
+                    if let Some(function) = self.$method.as_mut() {
+                        function($($arg)?);
+                    }
+                }
+            )*
+        }
+    };
+}
+fn_handler! {
+    area(area: &Area),
+    changeset(changeset: &Changeset),
+    changeset_discussion(changeset_discussion: &ChangesetDiscussion),
+    inner_ring(inner_ring: &InnerRing),
+    node(node: &Node),
+    osm_object(object: &OSMObject),
+    outer_ring(outer_ring: &OuterRing),
+    relation(relation: &Relation),
+    relation_member_list(relation_member_list: &RelationMemberList),
+    tag_list(tag_list: &TagList),
+    way(way: &Way),
+    way_node_list(way_node_list: &WayNodeList),
+    flush(),
+    progress(progress: &Progress),
 }
 
 /// The handler table is a virtual function table, comparable to `dyn Handler`.
@@ -189,6 +417,87 @@ pub struct HandlerTable<'a> {
     way: *const (),
     way_node_list: *const (),
     flush: *const (),
+    progress: *const (),
+}
+
+/// Progress made reading a file reported to [`Handler::progress`]
+///
+/// For processes which read the file multiple times (i.e. [`apply_with_areas`](Handler::apply_with_areas)),
+/// the passes are concatenated: the offset and size cover the sum of all passes.
+#[repr(C)]
+#[derive(Copy, Clone, Debug)]
+pub struct Progress {
+    /// Number of bytes read so far
+    pub offset: u64,
+
+    /// Total number of bytes to read
+    ///
+    /// This is zero, if the size isn't known, for example when reading from a pipe.
+    pub file_size: u64,
+}
+
+impl Progress {
+    /// Get the progress as percentage between 0 and 100, if the size is known.
+    pub fn percent(&self) -> Option<u8> {
+        (self.offset.min(self.file_size) * 100)
+            .checked_div(self.file_size)
+            .map(|percent| percent as u8)
+    }
+}
+
+/// A [Handler] which draws a progress bar to stderr
+///
+/// It mimics `osmium::ProgressBar` and is intended to be combined with other handlers:
+///
+/// ```no_run
+/// use libosmium::handler::{from_fns, Handler, ProgressBar};
+///
+/// let mut nodes = 0;
+/// let counter = from_fns().node(|_node| nodes += 1);
+/// (counter, ProgressBar::new()).apply("planet.osm.pbf").unwrap();
+/// ```
+#[derive(Default, Debug)]
+pub struct ProgressBar {
+    /// The last percentage drawn
+    percent: Option<u8>,
+}
+
+impl ProgressBar {
+    /// Construct a new progress bar which hasn't drawn anything yet.
+    pub const fn new() -> Self {
+        ProgressBar { percent: None }
+    }
+
+    /// Draw the bar for a given percentage
+    fn draw(percent: u8) {
+        const WIDTH: usize = 70;
+        let filled = percent as usize * WIDTH / 100;
+        eprint!(
+            "\r[{:=<filled$}{:>empty$}] {percent:>3}% ",
+            "",
+            "",
+            filled = filled,
+            empty = WIDTH - filled,
+        );
+    }
+}
+
+impl Handler for ProgressBar {
+    fn flush(&mut self) {
+        if self.percent.take().is_some() {
+            Self::draw(100);
+            eprintln!();
+        }
+    }
+
+    fn progress(&mut self, progress: &Progress) {
+        if let Some(percent) = progress.percent() {
+            if self.percent != Some(percent) {
+                self.percent = Some(percent);
+                Self::draw(percent);
+            }
+        }
+    }
 }
 
 /// This struct holds the varies parameter controlling how areas are assembled.
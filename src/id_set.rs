@@ -0,0 +1,361 @@
+//! defines sets of object ids to collect references with and the [IdFilter] to select objects by them.
+//!
+//! The sets mirror libosmium's `osmium::index::IdSetDense` and `osmium::index::IdSetSmall`
+//! and [NwrArray] mirrors `osmium::nwr_array` which stores one set per object type.
+//!
+//! A typical use are two passes through a file: The first one collects the ids of referenced objects
+//! and the second one filters for them.
+//!
+//! ```no_run
+//! use libosmium::handler::{from_fns, Handler};
+//! use libosmium::id_set::{IdFilter, IdSet, IdSetDense, NwrArray};
+//! use libosmium::ItemType;
+//!
+//! // Collect all highways and their nodes
+//! let mut ids = NwrArray::<IdSetDense>::default();
+//! from_fns()
+//!     .way(|way| {
+//!         if way.tags().get("highway").is_some() {
+//!             ids[ItemType::Way].set(way.positive_id());
+//!             ids.add_way_nodes(way.nodes());
+//!         }
+//!     })
+//!     .apply("planet.osm.pbf")?;
+//!
+//! // Process only the collected objects
+//! let mut nodes = 0;
+//! IdFilter::new(&ids, from_fns().node(|_node| nodes += 1)).apply("planet.osm.pbf")?;
+//! # Ok::<(), libosmium::io::Error>(())
+//! ```
+
+use std::ops::{Index, IndexMut};
+
+use crate::handler::{Changeset, Handler, Progress};
+use crate::item::ItemType;
+use crate::node_ref_list::WayNodeList;
+use crate::object::{OSMObject, UnsignedObjectId};
+use crate::relation::RelationMemberList;
+
+/// A set of (unsigned) object ids
+///
+/// Negative ids are stored using their absolute value, like libosmium does
+/// (see [`OSMObject::positive_id`]).
+pub trait IdSet {
+    /// Add an id to the set.
+    fn set(&mut self, id: UnsignedObjectId);
+
+    /// Check whether an id is in the set.
+    fn get(&self, id: UnsignedObjectId) -> bool;
+
+    /// Is the set empty?
+    fn is_empty(&self) -> bool;
+
+    /// Get the number of ids in the set.
+    fn len(&self) -> usize;
+
+    /// Remove all ids from the set.
+    fn clear(&mut self);
+}
+
+/// Number of bits used to index an id within its chunk
+const CHUNK_BITS: u32 = 22;
+
+/// Number of ids stored per chunk
+const CHUNK_SIZE: UnsignedObjectId = 1 << CHUNK_BITS;
+
+/// Id set storing one bit per possible id
+///
+/// The bits are allocated in chunks of 4 million ids (512 KB) when an id from the chunk is set.
+/// This is the right set for large amounts of ids, for example all nodes of a country's ways.
+#[derive(Clone, Debug, Default)]
+pub struct IdSetDense {
+    chunks: Vec<Option<Box<[u8]>>>,
+    size: usize,
+}
+
+impl IdSetDense {
+    /// Create a new, empty set.
+    pub const fn new() -> Self {
+        Self {
+            chunks: Vec::new(),
+            size: 0,
+        }
+    }
+
+    /// Split an id into its chunk's index, the byte's index in the chunk and the bit mask in the byte
+    fn position(id: UnsignedObjectId) -> (usize, usize, u8) {
+        let chunk = (id >> CHUNK_BITS) as usize;
+        let offset = id & (CHUNK_SIZE - 1);
+        (chunk, (offset >> 3) as usize, 1 << (offset & 7))
+    }
+
+    /// Add an id to the set and return whether it hasn't been in the set before.
+    pub fn check_and_set(&mut self, id: UnsignedObjectId) -> bool {
+        let (chunk, byte, bit) = Self::position(id);
+        if chunk >= self.chunks.len() {
+            self.chunks.resize(chunk + 1, None);
+        }
+        let chunk = self.chunks[chunk]
+            .get_or_insert_with(|| vec![0; (CHUNK_SIZE / 8) as usize].into_boxed_slice());
+        if chunk[byte] & bit == 0 {
+            chunk[byte] |= bit;
+            self.size += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Remove an id from the set.
+    pub fn unset(&mut self, id: UnsignedObjectId) {
+        let (chunk, byte, bit) = Self::position(id);
+        if let Some(Some(chunk)) = self.chunks.get_mut(chunk) {
+            if chunk[byte] & bit != 0 {
+                chunk[byte] &= !bit;
+                self.size -= 1;
+            }
+        }
+    }
+
+    /// Return an iterator over the ids in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = UnsignedObjectId> + '_ {
+        self.chunks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, chunk)| Some((index, chunk.as_ref()?)))
+            .flat_map(|(index, chunk)| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, byte)| **byte != 0)
+                    .flat_map(move |(byte_index, byte)| {
+                        (0..8)
+                            .filter(move |bit| byte & (1 << bit) != 0)
+                            .map(move |bit| {
+                                ((index as UnsignedObjectId) << CHUNK_BITS)
+                                    | ((byte_index as UnsignedObjectId) << 3)
+                                    | bit
+                            })
+                    })
+            })
+    }
+}
+
+impl IdSet for IdSetDense {
+    fn set(&mut self, id: UnsignedObjectId) {
+        self.check_and_set(id);
+    }
+
+    fn get(&self, id: UnsignedObjectId) -> bool {
+        let (chunk, byte, bit) = Self::position(id);
+        match self.chunks.get(chunk) {
+            Some(Some(chunk)) => chunk[byte] & bit != 0,
+            _ => false,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.size == 0
+    }
+
+    fn len(&self) -> usize {
+        self.size
+    }
+
+    fn clear(&mut self) {
+        self.chunks.clear();
+        self.size = 0;
+    }
+}
+
+/// Id set storing the ids in a vector
+///
+/// This is the right set for few ids, for example the members of some relations.
+/// Lookups are binary searches after [`sort_unique`](IdSetSmall::sort_unique) has been called
+/// and linear searches otherwise.
+#[derive(Clone, Debug)]
+pub struct IdSetSmall {
+    ids: Vec<UnsignedObjectId>,
+    sorted: bool,
+}
+
+impl IdSetSmall {
+    /// Create a new, empty set.
+    pub const fn new() -> Self {
+        Self {
+            ids: Vec::new(),
+            sorted: true,
+        }
+    }
+
+    /// Sort the ids and remove duplicates to make lookups fast.
+    pub fn sort_unique(&mut self) {
+        if !self.sorted {
+            self.ids.sort_unstable();
+            self.ids.dedup();
+            self.sorted = true;
+        }
+    }
+
+    /// Return an iterator over the ids.
+    ///
+    /// The ids are in ascending order after [`sort_unique`](IdSetSmall::sort_unique) has been called.
+    pub fn iter(&self) -> impl Iterator<Item = UnsignedObjectId> + '_ {
+        self.ids.iter().copied()
+    }
+}
+
+impl Default for IdSetSmall {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl IdSet for IdSetSmall {
+    fn set(&mut self, id: UnsignedObjectId) {
+        // Skip the most common duplicates without sorting
+        if self.ids.last() != Some(&id) {
+            self.sorted &= self.ids.last().is_none_or(|last| *last < id);
+            self.ids.push(id);
+        }
+    }
+
+    fn get(&self, id: UnsignedObjectId) -> bool {
+        if self.sorted {
+            self.ids.binary_search(&id).is_ok()
+        } else {
+            self.ids.contains(&id)
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+
+    /// Get the number of ids in the set.
+    ///
+    /// Duplicates are only removed by [`sort_unique`](IdSetSmall::sort_unique).
+    fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    fn clear(&mut self) {
+        self.ids.clear();
+        self.sorted = true;
+    }
+}
+
+/// Array storing one value for each of the types node, way and relation
+///
+/// It can be indexed with an [ItemType]. Indexing with any other type than
+/// [`Node`](ItemType::Node), [`Way`](ItemType::Way) or [`Relation`](ItemType::Relation) panics.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct NwrArray<T>(pub [T; 3]);
+
+impl<T> NwrArray<T> {
+    /// Get the value for an item type, if it is a node, way or relation.
+    pub fn get(&self, item_type: ItemType) -> Option<&T> {
+        self.0.get(nwr_index(item_type)?)
+    }
+
+    /// Get the value for an item type mutably, if it is a node, way or relation.
+    pub fn get_mut(&mut self, item_type: ItemType) -> Option<&mut T> {
+        self.0.get_mut(nwr_index(item_type)?)
+    }
+}
+
+/// Position of an item type in an [NwrArray]
+fn nwr_index(item_type: ItemType) -> Option<usize> {
+    match item_type {
+        ItemType::Node => Some(0),
+        ItemType::Way => Some(1),
+        ItemType::Relation => Some(2),
+        _ => None,
+    }
+}
+
+impl<T> Index<ItemType> for NwrArray<T> {
+    type Output = T;
+
+    fn index(&self, item_type: ItemType) -> &Self::Output {
+        self.get(item_type)
+            .unwrap_or_else(|| panic!("{item_type:?} is not a node, way or relation"))
+    }
+}
+
+impl<T> IndexMut<ItemType> for NwrArray<T> {
+    fn index_mut(&mut self, item_type: ItemType) -> &mut Self::Output {
+        self.get_mut(item_type)
+            .unwrap_or_else(|| panic!("{item_type:?} is not a node, way or relation"))
+    }
+}
+
+impl<S: IdSet> NwrArray<S> {
+    /// Add the ids of a way's nodes to the node set.
+    pub fn add_way_nodes(&mut self, nodes: &WayNodeList) {
+        let set = &mut self[ItemType::Node];
+        for node in nodes.iter() {
+            set.set(node.id.unsigned_abs());
+        }
+    }
+
+    /// Add the ids of a relation's members to the set of their types.
+    ///
+    /// Members which aren't nodes, ways or relations are ignored.
+    pub fn add_members(&mut self, members: &RelationMemberList) {
+        for member in members {
+            if let Some(set) = self.get_mut(member.member_type()) {
+                set.set(member.member_ref().unsigned_abs());
+            }
+        }
+    }
+
+    /// Check whether an object's id is in the set of its type.
+    ///
+    /// Objects which aren't nodes, ways or relations are never contained.
+    pub fn contains(&self, object: &OSMObject) -> bool {
+        self.get(object.as_ref().item_type())
+            .is_some_and(|set| set.get(object.positive_id()))
+    }
+}
+
+/// Adapter passing only the nodes, ways and relations whose id is in an [NwrArray] of [IdSets](IdSet) on to a [Handler]
+///
+/// Other items like areas and changesets are passed on unfiltered.
+pub struct IdFilter<'s, S, H> {
+    ids: &'s NwrArray<S>,
+    handler: H,
+}
+
+impl<'s, S: IdSet, H: Handler> IdFilter<'s, S, H> {
+    /// Filter the items passed on to a handler.
+    pub fn new(ids: &'s NwrArray<S>, handler: H) -> Self {
+        Self { ids, handler }
+    }
+
+    /// Unwrap the handler
+    pub fn into_inner(self) -> H {
+        self.handler
+    }
+}
+
+impl<S: IdSet, H: Handler> Handler for IdFilter<'_, S, H> {
+    fn osm_object(&mut self, object: &OSMObject) {
+        let item = object.as_ref();
+        if self.ids.get(item.item_type()).is_none() || self.ids.contains(object) {
+            self.handler.apply_item(item);
+        }
+    }
+
+    fn changeset(&mut self, changeset: &Changeset) {
+        self.handler.apply_item(changeset.as_ref());
+    }
+
+    fn flush(&mut self) {
+        self.handler.flush();
+    }
+
+    fn progress(&mut self, progress: &Progress) {
+        self.handler.progress(progress);
+    }
+}
@@ -0,0 +1,566 @@
+//! defines the [File] to read osm data from and the [Reader] which processes it using a [Handler].
+//!
+//! Besides paths, a [File] can be created from stdin, an in-memory byte slice,
+//! a file descriptor or any type implementing [Read].
+//! The latter two rely on `/dev/fd` and are therefore only available on unix.
+
+use std::borrow::Cow;
+use std::ffi::{CStr, CString, NulError};
+use std::fmt;
+use std::io::Read;
+#[cfg(unix)]
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd};
+use std::os::raw::c_char;
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::ptr;
+#[cfg(unix)]
+use std::thread;
+
+use crate::handler::{
+    apply, apply_relations, apply_with_areas, apply_with_way_areas, apply_with_ways, catch_error,
+    AreaAssemblerConfig, Handler,
+};
+
+/// A source of osm data
+///
+/// This mirrors `osmium::io::File` which stores a file's name (or buffer) together with its format.
+/// Supported are PBF, OSM XML and OsmChange (`.osc`) files, optionally gzip compressed.
+pub struct File<'a> {
+    source: Source<'a>,
+    format: Option<String>,
+}
+
+/// The different kinds of sources a [File] can read from
+enum Source<'a> {
+    Path(PathBuf),
+    Stdin,
+    Bytes(Cow<'a, [u8]>),
+    #[cfg(unix)]
+    Fd(BorrowedFd<'a>),
+    #[cfg(unix)]
+    Read(Box<dyn Read + Send + 'a>),
+}
+
+impl<'a> File<'a> {
+    /// Read from a file on disk.
+    ///
+    /// The format is detected from the file's suffix.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            source: Source::Path(path.as_ref().to_path_buf()),
+            format: None,
+        }
+    }
+
+    /// Read from stdin.
+    ///
+    /// The format defaults to PBF.
+    pub fn stdin() -> Self {
+        Self {
+            source: Source::Stdin,
+            format: None,
+        }
+    }
+
+    /// Read from a slice of bytes in memory without copying them.
+    ///
+    /// The format defaults to PBF.
+    pub fn from_bytes(bytes: &'a [u8]) -> Self {
+        Self {
+            source: Source::Bytes(Cow::Borrowed(bytes)),
+            format: None,
+        }
+    }
+
+    /// Read from an open file descriptor.
+    ///
+    /// libosmium opens the descriptor again as `/dev/fd/N`.
+    /// Pipes and sockets are therefore read from their current position,
+    /// but regular files are read from their start, regardless of the descriptor's offset.
+    /// The format defaults to PBF.
+    #[cfg(unix)]
+    pub fn from_fd(fd: BorrowedFd<'a>) -> Self {
+        Self {
+            source: Source::Fd(fd),
+            format: None,
+        }
+    }
+
+    /// Read from any [Read] implementation like a decompression stream or a http body.
+    ///
+    /// The data is copied on a background thread into a pipe libosmium reads from.
+    /// The format defaults to PBF.
+    #[cfg(unix)]
+    pub fn from_reader(reader: impl Read + Send + 'a) -> Self {
+        Self {
+            source: Source::Read(Box::new(reader)),
+            format: None,
+        }
+    }
+
+    /// Set the file's format explicitly instead of detecting it.
+    ///
+    /// The format uses libosmium's syntax for example `"pbf"` or `"osm.pbf"`.
+    pub fn with_format(mut self, format: &str) -> Self {
+        self.format = Some(format.to_string());
+        self
+    }
+
+    /// Can the source be read more than once?
+    fn is_rereadable(&self) -> bool {
+        matches!(self.source, Source::Path(_) | Source::Bytes(_))
+    }
+
+    /// Read the entire source into memory to be able to read it more than once
+    pub(crate) fn into_rereadable(self) -> Result<Self, Error> {
+        if self.is_rereadable() {
+            return Ok(self);
+        }
+
+        let mut bytes = Vec::new();
+        match self.source {
+            Source::Path(_) | Source::Bytes(_) => unreachable!("Checked by is_rereadable"),
+            Source::Stdin => {
+                std::io::stdin().lock().read_to_end(&mut bytes)?;
+            }
+            #[cfg(unix)]
+            Source::Fd(fd) => {
+                // Open the descriptor like libosmium does, so regular files are read from their start as well
+                let path = fd_path(fd);
+                std::fs::File::open(std::ffi::OsStr::from_bytes(path.as_bytes()))?
+                    .read_to_end(&mut bytes)?;
+            }
+            #[cfg(unix)]
+            Source::Read(mut reader) => {
+                reader.read_to_end(&mut bytes)?;
+            }
+        }
+        Ok(Self {
+            source: Source::Bytes(Cow::Owned(bytes)),
+            format: self.format,
+        })
+    }
+
+    /// Borrow a file returned by [`into_rereadable`](File::into_rereadable) to read it once more
+    pub(crate) fn reread(&self) -> File<'_> {
+        let source = match &self.source {
+            Source::Path(path) => Source::Path(path.clone()),
+            Source::Bytes(bytes) => Source::Bytes(Cow::Borrowed(bytes)),
+            _ => panic!("Only paths and bytes can be read more than once"),
+        };
+        File {
+            source,
+            format: self.format.clone(),
+        }
+    }
+
+    /// Open the source and pass it to an ffi function which reports exceptions using an error buffer
+    ///
+    /// See [`catch_error`].
+    pub(crate) fn read(self, function: impl FnOnce(&FileSpec, *mut c_char)) -> Result<(), Error> {
+        self.open(|file| Ok(catch_error(|error| function(&file, error))?))
+    }
+
+    /// Open the source and pass its description to a function
+    ///
+    /// The description is only valid while the function runs.
+    fn open(self, function: impl FnOnce(FileSpec) -> Result<(), Error>) -> Result<(), Error> {
+        let format = match self.format {
+            Some(format) => CString::new(format)?,
+            None if matches!(self.source, Source::Path(_)) => CString::default(),
+            None => CString::from(c"pbf"),
+        };
+
+        match self.source {
+            Source::Path(path) => {
+                let path = path_to_cstring(&path)?;
+                function(FileSpec::path(&path, &format))
+            }
+            Source::Stdin => function(FileSpec::path(c"-", &format)),
+            Source::Bytes(bytes) => function(FileSpec::bytes(&bytes, &format)),
+            #[cfg(unix)]
+            Source::Fd(fd) => {
+                let path = fd_path(fd);
+                function(FileSpec::path(&path, &format))
+            }
+            #[cfg(unix)]
+            Source::Read(mut reader) => {
+                let (pipe_reader, mut pipe_writer) = pipe()?;
+                thread::scope(|scope| {
+                    let copy = scope.spawn(move || std::io::copy(&mut reader, &mut pipe_writer));
+
+                    let path = fd_path(pipe_reader.as_fd());
+                    let result = function(FileSpec::path(&path, &format));
+                    // Closing the pipe stops the copy, if libosmium didn't read everything
+                    drop(pipe_reader);
+
+                    let copied = copy
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+                    // A libosmium error takes precedence over the resulting broken pipe
+                    result?;
+                    copied?;
+                    Ok(())
+                })
+            }
+        }
+    }
+}
+
+/// Open several files at once and pass them to an ffi function which reports exceptions using an error buffer
+///
+/// See [`catch_error`].
+fn read_files(
+    files: Vec<File<'_>>,
+    merge: bool,
+    function: impl FnOnce(&Files, *mut c_char),
+) -> Result<(), Error> {
+    /// Open the remaining files recursively, so all of them stay open until `function` returns
+    fn open_all(
+        files: &mut dyn Iterator<Item = File<'_>>,
+        specs: &mut Vec<FileSpec>,
+        function: &mut dyn FnMut(&[FileSpec]) -> Result<(), Error>,
+    ) -> Result<(), Error> {
+        match files.next() {
+            None => function(specs),
+            Some(file) => file.open(|spec| {
+                specs.push(spec);
+                open_all(files, specs, function)
+            }),
+        }
+    }
+
+    let mut function = Some(function);
+    open_all(&mut files.into_iter(), &mut Vec::new(), &mut |specs| {
+        let function = function.take().expect("open_all calls function once");
+        let files = Files {
+            specs: specs.as_ptr(),
+            count: specs.len(),
+            merge,
+        };
+        Ok(catch_error(|error| function(&files, error))?)
+    })
+}
+
+impl fmt::Debug for File<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("File");
+        match &self.source {
+            Source::Path(path) => debug.field("path", path),
+            Source::Stdin => debug.field("stdin", &true),
+            Source::Bytes(bytes) => debug.field("bytes", &bytes.len()),
+            #[cfg(unix)]
+            Source::Fd(fd) => debug.field("fd", fd),
+            #[cfg(unix)]
+            Source::Read(_) => debug.field("reader", &".."),
+        };
+        debug.field("format", &self.format).finish()
+    }
+}
+
+/// Convert a path into a string libosmium can open
+#[cfg(unix)]
+fn path_to_cstring(path: &Path) -> Result<CString, Error> {
+    Ok(CString::new(path.as_os_str().as_bytes())?)
+}
+
+/// Convert a path into a string libosmium can open
+///
+/// Outside of unix paths aren't bytes, so only paths which are valid UTF-8 can be passed on.
+#[cfg(not(unix))]
+fn path_to_cstring(path: &Path) -> Result<CString, Error> {
+    let path = path
+        .to_str()
+        .ok_or_else(|| Error::NonUtf8Path(path.to_path_buf()))?;
+    Ok(CString::new(path)?)
+}
+
+/// Create a pipe returning its reading and writing end
+#[cfg(unix)]
+fn pipe() -> std::io::Result<(OwnedFd, std::fs::File)> {
+    let mut fds = [0; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    unsafe {
+        Ok((
+            OwnedFd::from_raw_fd(fds[0]),
+            std::fs::File::from_raw_fd(fds[1]),
+        ))
+    }
+}
+
+/// Path to an open file descriptor which libosmium can open
+#[cfg(unix)]
+fn fd_path(fd: BorrowedFd<'_>) -> CString {
+    CString::new(format!("/dev/fd/{}", fd.as_raw_fd())).expect("Formatted integer contains no NUL")
+}
+
+/// Processes a [File] using a [Handler]
+///
+/// ```no_run
+/// use libosmium::handler::Handler;
+/// use libosmium::io::{File, Reader};
+/// use libosmium::Node;
+///
+/// struct CountNodes(usize);
+/// impl Handler for CountNodes {
+///     fn node(&mut self, _node: &Node) {
+///         self.0 += 1;
+///     }
+/// }
+///
+/// let uploaded: Vec<u8> = Vec::new(); // e.g. a http body
+/// let mut handler = CountNodes(0);
+/// Reader::new(File::from_bytes(&uploaded)).apply(&mut handler)?;
+/// # Ok::<(), libosmium::io::Error>(())
+/// ```
+///
+/// A reader can also process several files as one logical stream,
+/// for example the tiles of a region:
+///
+/// ```no_run
+/// # use libosmium::handler::Handler;
+/// # use libosmium::io::{File, Reader};
+/// # let mut handler = libosmium::handler::from_fns();
+/// Reader::from_files(["north.osm.pbf", "south.osm.pbf"].map(File::new))
+///     .merge()
+///     .apply(&mut handler)?;
+/// # Ok::<(), libosmium::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Reader<'a> {
+    files: Vec<File<'a>>,
+    merge: bool,
+}
+
+impl<'a> Reader<'a> {
+    /// Create a reader for a file.
+    pub fn new(file: File<'a>) -> Self {
+        Self::from_files([file])
+    }
+
+    /// Create a reader for several files which are read one after another.
+    ///
+    /// The handler is [flushed](Handler::flush) once after the last file.
+    pub fn from_files(files: impl IntoIterator<Item = File<'a>>) -> Self {
+        Self {
+            files: files.into_iter().collect(),
+            merge: false,
+        }
+    }
+
+    /// Merge the files' objects instead of reading the files one after another.
+    ///
+    /// Like `osmium merge` the objects are [merge sorted](crate::object_order::object_order_type_id_version)
+    /// and objects with the same type, id and version are only processed once (taken from the earliest file).
+    /// Therefore, every file has to be sorted already, which files from planet dumps or extracts usually are.
+    ///
+    /// Merging requires all files to be open at the same time.
+    pub fn merge(mut self) -> Self {
+        self.merge = true;
+        self
+    }
+
+    /// Read the files and process their items using a handler.
+    ///
+    /// See [`Handler::apply`].
+    pub fn apply<H: Handler>(self, handler: &mut H) -> Result<(), Error> {
+        read_files(self.files, self.merge, |files, error| unsafe {
+            apply(handler.as_table(), files, error)
+        })
+    }
+
+    /// Read only the files' relations and process them using a handler.
+    ///
+    /// Like libosmium's `osmium::relations::read_relations` the other objects aren't even decoded.
+    pub(crate) fn apply_relations<H: Handler>(self, handler: &mut H) -> Result<(), Error> {
+        read_files(self.files, self.merge, |files, error| unsafe {
+            apply_relations(handler.as_table(), files, error)
+        })
+    }
+
+    /// Read the files, populate a way's nodes' locations and process the items using a handler.
+    ///
+    /// See [`Handler::apply_with_ways`].
+    pub fn apply_with_ways<H: Handler>(self, handler: &mut H) -> Result<(), Error> {
+        read_files(self.files, self.merge, |files, error| unsafe {
+            apply_with_ways(handler.as_table(), files, error)
+        })
+    }
+
+    /// Read the files, assemble areas and process the items using a handler.
+    ///
+    /// See [`Handler::apply_with_areas`].
+    ///
+    /// Assembling areas requires reading the file twice.
+    /// Therefore, sources which can only be read once (stdin, file descriptors and readers)
+    /// are read into memory entirely first.
+    pub fn apply_with_areas<H: Handler>(
+        self,
+        handler: &mut H,
+        config: AreaAssemblerConfig,
+    ) -> Result<(), Error> {
+        let files = self
+            .files
+            .into_iter()
+            .map(File::into_rereadable)
+            .collect::<Result<_, _>>()?;
+        read_files(files, self.merge, |files, error| unsafe {
+            apply_with_areas(handler.as_table(), files, error, config)
+        })
+    }
+
+    /// Read the files, assemble areas from closed ways only and process the items using a handler.
+    ///
+    /// See [`Handler::apply_with_way_areas`].
+    ///
+    /// Unlike [`apply_with_areas`](Reader::apply_with_areas) this reads the files only once,
+    /// so every source can be read without buffering it.
+    pub fn apply_with_way_areas<H: Handler>(
+        self,
+        handler: &mut H,
+        config: AreaAssemblerConfig,
+    ) -> Result<(), Error> {
+        read_files(self.files, self.merge, |files, error| unsafe {
+            apply_with_way_areas(handler.as_table(), files, error, config)
+        })
+    }
+}
+
+/// Error reading a [File]
+#[derive(Debug)]
+pub enum Error {
+    /// An exception thrown by libosmium
+    Osmium(CString),
+
+    /// An error while reading the source on rust's side
+    Io(std::io::Error),
+
+    /// The file's path or format contains a NUL character
+    Nul(NulError),
+
+    /// The file's path isn't valid UTF-8, which is required outside of unix
+    NonUtf8Path(PathBuf),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Osmium(message) => write!(f, "{}", message.to_string_lossy()),
+            Error::Io(error) => write!(f, "{error}"),
+            Error::Nul(error) => write!(f, "{error}"),
+            Error::NonUtf8Path(path) => write!(f, "path is not valid UTF-8: {}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Osmium(_) | Error::NonUtf8Path(_) => None,
+            Error::Io(error) => Some(error),
+            Error::Nul(error) => Some(error),
+        }
+    }
+}
+
+impl From<CString> for Error {
+    fn from(message: CString) -> Self {
+        Error::Osmium(message)
+    }
+}
+
+impl From<NulError> for Error {
+    fn from(error: NulError) -> Self {
+        Error::Nul(error)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(error: std::io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+/// Description of a file passed to c++ which turns it into a `osmium::io::File`
+///
+/// If `buffer` is not null, the file is read from it. Otherwise `path` is opened.
+/// The pointers are only valid while the described [File] is [opened](File::open).
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub(crate) struct FileSpec {
+    path: *const c_char,
+    format: *const c_char,
+    buffer: *const u8,
+    buffer_size: usize,
+}
+
+impl FileSpec {
+    /// Describe a file on disk (or `"-"` for stdin)
+    fn path(path: &CStr, format: &CStr) -> Self {
+        Self {
+            path: path.as_ptr(),
+            format: format.as_ptr(),
+            buffer: ptr::null(),
+            buffer_size: 0,
+        }
+    }
+
+    /// Describe a file in memory
+    fn bytes(bytes: &[u8], format: &CStr) -> Self {
+        Self {
+            path: c"".as_ptr(),
+            format: format.as_ptr(),
+            buffer: bytes.as_ptr(),
+            buffer_size: bytes.len(),
+        }
+    }
+}
+
+/// List of files passed to c++ which reads them one after another or merged
+#[repr(C)]
+pub(crate) struct Files {
+    specs: *const FileSpec,
+    count: usize,
+    merge: bool,
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    /// Get the bytes of a file returned by [`File::into_rereadable`]
+    fn rereadable_bytes(file: File<'_>) -> Vec<u8> {
+        match file.into_rereadable().unwrap().source {
+            Source::Bytes(bytes) => bytes.into_owned(),
+            _ => panic!("Expected the file to be read into memory"),
+        }
+    }
+
+    #[test]
+    fn rereadable_fd_reads_regular_files_from_start() {
+        let path = std::env::temp_dir().join(format!("libosmium-fd-{}", std::process::id()));
+        std::fs::write(&path, b"0123456789").unwrap();
+        let mut file = std::fs::File::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        file.read_exact(&mut [0; 4]).unwrap();
+        assert_eq!(rereadable_bytes(File::from_fd(file.as_fd())), b"0123456789");
+    }
+
+    #[test]
+    fn rereadable_fd_reads_pipes_from_current_position() {
+        let (reader, mut writer) = pipe().unwrap();
+        writer.write_all(b"0123456789").unwrap();
+        drop(writer);
+
+        let mut reader = std::fs::File::from(reader);
+        reader.read_exact(&mut [0; 4]).unwrap();
+        assert_eq!(rereadable_bytes(File::from_fd(reader.as_fd())), b"456789");
+    }
+}
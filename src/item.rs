@@ -1,26 +1,48 @@
 use std::mem::transmute;
 
 use crate::area::Area;
-use crate::handler::{Changeset, ChangesetDiscussion, Relation, RelationMemberList};
+use crate::handler::{Changeset, ChangesetDiscussion};
 use crate::node::Node;
 use crate::node_ref_list::{InnerRing, OuterRing, WayNodeList};
+use crate::object::OSMObject;
+use crate::relation::{Relation, RelationMemberList};
 use crate::tag_list::TagList;
 use crate::way::Way;
 
 /// This type is the base class responsible for libosmium's custom memory management.
 ///
 /// It stores an object's dynamic size and its actual subclass as an enum.
+///
+/// Like the other c++ types it is opaque, so safe code can't move or overwrite an item's header.
+pub enum Item {}
+
+/// The header every item starts with
 #[repr(C)]
-pub struct Item {
-    _size: ItemSize,
-    _type: ItemType,
-    _flags_and_padding: u16,
+struct ItemHeader {
+    size: ItemSize,
+    item_type: ItemType,
+    flags_and_padding: u16,
 }
 
 /// Align items to this many bytes
-const ALIGN_BYTES: usize = 8;
+pub(crate) const ALIGN_BYTES: usize = 8;
+
+/// Bit in `flags_and_padding` marking an item as removed
+///
+/// In c++ the flags are declared as bit fields with `removed` being the first one.
+const REMOVED_FLAG: u16 = 1;
 
 impl Item {
+    /// Get the item's header
+    fn header(&self) -> &ItemHeader {
+        unsafe { &*(self as *const Item as *const ItemHeader) }
+    }
+
+    /// Get the item's header mutably
+    fn header_mut(&mut self) -> &mut ItemHeader {
+        unsafe { &mut *(self as *mut Item as *mut ItemHeader) }
+    }
+
     /// Get the item's data as byte slice padded to [`aligned_size`](Self::aligned_size)
     pub fn as_bytes(&self) -> &[u8] {
         unsafe { std::slice::from_raw_parts(self as *const Item as *const u8, self.aligned_size()) }
@@ -28,23 +50,39 @@ impl Item {
 
     /// Get the item's dynamic size aligned to `ALIGN_BYTES`
     pub fn aligned_size(&self) -> usize {
-        (self._size as usize + ALIGN_BYTES - 1) & !(ALIGN_BYTES - 1)
+        (self.header().size as usize + ALIGN_BYTES - 1) & !(ALIGN_BYTES - 1)
     }
 
     /// Get the item's dynamic size
     pub fn byte_size(&self) -> ItemSize {
-        self._size
+        self.header().size
     }
 
     /// Get the item's type
     pub fn item_type(&self) -> ItemType {
-        self._type
+        self.header().item_type
+    }
+
+    /// Is this item marked as removed?
+    ///
+    /// Removed items stay in their buffer until it is [purged](crate::ItemBuffer::purge_removed).
+    pub fn removed(&self) -> bool {
+        self.header().flags_and_padding & REMOVED_FLAG != 0
+    }
+
+    /// Mark this item as removed or not removed.
+    pub fn set_removed(&mut self, removed: bool) {
+        if removed {
+            self.header_mut().flags_and_padding |= REMOVED_FLAG;
+        } else {
+            self.header_mut().flags_and_padding &= !REMOVED_FLAG;
+        }
     }
 
     /// Convert an item's reference into a reference of its actual subclass
     pub fn cast(&self) -> Option<ItemRef> {
         unsafe {
-            Some(match self._type {
+            Some(match self.item_type() {
                 ItemType::Node => ItemRef::Node(transmute(self)),
                 ItemType::Way => ItemRef::Way(transmute(self)),
                 ItemType::Relation => ItemRef::Relation(transmute(self)),
@@ -63,6 +101,124 @@ impl Item {
             })
         }
     }
+
+    /// Cast an item's reference into a reference of a specific subclass, if it is an instance of it.
+    pub fn downcast<T: ItemSubclass>(&self) -> Option<&T> {
+        if T::is_instance(self.item_type()) {
+            Some(unsafe { &*(self as *const Item as *const T) })
+        } else {
+            None
+        }
+    }
+
+    /// Cast an item's mutable reference into a mutable reference of a specific subclass, if it is an instance of it.
+    pub fn downcast_mut<T: ItemSubclass>(&mut self) -> Option<&mut T> {
+        if T::is_instance(self.item_type()) {
+            Some(unsafe { &mut *(self as *mut Item as *mut T) })
+        } else {
+            None
+        }
+    }
+
+    /// Convert an item's mutable reference into a mutable reference of its actual subclass
+    pub fn cast_mut(&mut self) -> Option<ItemRefMut<'_>> {
+        let ptr = self as *mut Item;
+        unsafe {
+            Some(match self.item_type() {
+                ItemType::Node => ItemRefMut::Node(&mut *ptr.cast()),
+                ItemType::Way => ItemRefMut::Way(&mut *ptr.cast()),
+                ItemType::Relation => ItemRefMut::Relation(&mut *ptr.cast()),
+                ItemType::Area => ItemRefMut::Area(&mut *ptr.cast()),
+                ItemType::Changeset => ItemRefMut::Changeset(&mut *ptr.cast()),
+                ItemType::TagList => ItemRefMut::TagList(&mut *ptr.cast()),
+                ItemType::WayNodeList => ItemRefMut::WayNodeList(&mut *ptr.cast()),
+                ItemType::RelationMemberList => ItemRefMut::RelationMemberList(&mut *ptr.cast()),
+                ItemType::RelationMemberListWithFullMembers => {
+                    ItemRefMut::RelationMemberList(&mut *ptr.cast())
+                }
+                ItemType::OuterRing => ItemRefMut::OuterRing(&mut *ptr.cast()),
+                ItemType::InnerRing => ItemRefMut::InnerRing(&mut *ptr.cast()),
+                ItemType::ChangesetDiscussion => ItemRefMut::ChangesetDiscussion(&mut *ptr.cast()),
+                _ => return None,
+            })
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Item {
+    /// Serialize the item as its actual subclass
+    ///
+    /// Only osm objects and their collections are supported. Other items produce an error.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.cast() {
+            Some(ItemRef::Node(node)) => node.serialize(serializer),
+            Some(ItemRef::Way(way)) => way.serialize(serializer),
+            Some(ItemRef::Relation(relation)) => relation.serialize(serializer),
+            Some(ItemRef::Area(area)) => area.serialize(serializer),
+            Some(ItemRef::TagList(tag_list)) => tag_list.serialize(serializer),
+            Some(ItemRef::WayNodeList(list)) => list.serialize(serializer),
+            Some(ItemRef::RelationMemberList(list)) => list.serialize(serializer),
+            Some(ItemRef::OuterRing(ring)) => ring.serialize(serializer),
+            Some(ItemRef::InnerRing(ring)) => ring.serialize(serializer),
+            _ => Err(serde::ser::Error::custom(format_args!(
+                "can't serialize item of type {:?}",
+                self.item_type()
+            ))),
+        }
+    }
+}
+
+impl AsRef<Item> for Item {
+    fn as_ref(&self) -> &Item {
+        self
+    }
+}
+
+impl AsMut<Item> for Item {
+    fn as_mut(&mut self) -> &mut Item {
+        self
+    }
+}
+
+/// A subclass of [Item] which can be identified by its [ItemType].
+///
+/// This enables casting an [Item] into a specific subclass without matching on an [ItemRef]
+/// (see [`Item::downcast`]) and is the basis for typed views on [buffers](crate::ItemBuffer::select).
+///
+/// # Safety
+///
+/// [`is_instance`](ItemSubclass::is_instance) must only return `true` for item types
+/// whose memory layout is a valid instance of `Self`.
+pub unsafe trait ItemSubclass: AsRef<Item> + AsMut<Item> {
+    /// Check whether an item of the given type is an instance of this class
+    fn is_instance(item_type: ItemType) -> bool;
+}
+
+macro_rules! impl_item_subclass {
+    ($($class:ty => $($item_type:ident)|+;)*) => {
+        $(
+            unsafe impl ItemSubclass for $class {
+                fn is_instance(item_type: ItemType) -> bool {
+                    matches!(item_type, $(ItemType::$item_type)|+)
+                }
+            }
+        )*
+    };
+}
+impl_item_subclass! {
+    Node => Node;
+    Way => Way;
+    Relation => Relation;
+    Area => Area;
+    Changeset => Changeset;
+    TagList => TagList;
+    WayNodeList => WayNodeList | OuterRing | InnerRing;
+    OuterRing => OuterRing;
+    InnerRing => InnerRing;
+    RelationMemberList => RelationMemberList | RelationMemberListWithFullMembers;
+    ChangesetDiscussion => ChangesetDiscussion;
+    OSMObject => Node | Way | Relation | Area;
 }
 
 /// Enum for converting an [Item] reference into a reference of its actual subclass.
@@ -102,13 +258,54 @@ pub enum ItemRef<'a> {
     ChangesetDiscussion(&'a ChangesetDiscussion),
 }
 
+/// Enum for converting a mutable [Item] reference into a mutable reference of its actual subclass.
+pub enum ItemRefMut<'a> {
+    /// A mutable [Node] reference
+    Node(&'a mut Node),
+
+    /// A mutable [Way] reference
+    Way(&'a mut Way),
+
+    /// A mutable [Relation] reference
+    Relation(&'a mut Relation),
+
+    /// A mutable [Area] reference
+    Area(&'a mut Area),
+
+    /// A mutable [Changeset] reference
+    Changeset(&'a mut Changeset),
+
+    /// A mutable [TagList] reference
+    TagList(&'a mut TagList),
+
+    /// A mutable [WayNodeList] reference
+    WayNodeList(&'a mut WayNodeList),
+
+    /// A mutable [RelationMemberList] reference
+    RelationMemberList(&'a mut RelationMemberList),
+
+    /// A mutable [OuterRing] reference
+    OuterRing(&'a mut OuterRing),
+
+    /// A mutable [InnerRing] reference
+    InnerRing(&'a mut InnerRing),
+
+    /// A mutable [ChangesetDiscussion] reference
+    ChangesetDiscussion(&'a mut ChangesetDiscussion),
+}
+
 /// Enum identifying an item's actual subclass
 ///
 /// Because libosmium needs to store different items next to each other in memory, they all store
 /// their subclass as an enum at the beginning right after the size.
 #[repr(u16)]
 #[non_exhaustive]
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd)]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 #[allow(missing_docs)]
 pub enum ItemType {
     Undefined = 0x00,
@@ -126,6 +323,32 @@ pub enum ItemType {
     ChangesetDiscussion = 0x80,
 }
 
+impl TryFrom<u16> for ItemType {
+    type Error = u16;
+
+    /// Convert libosmium's numeric representation back into an [ItemType]
+    ///
+    /// Unknown values are returned as error.
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        Ok(match value {
+            0x00 => ItemType::Undefined,
+            0x01 => ItemType::Node,
+            0x02 => ItemType::Way,
+            0x03 => ItemType::Relation,
+            0x04 => ItemType::Area,
+            0x05 => ItemType::Changeset,
+            0x11 => ItemType::TagList,
+            0x12 => ItemType::WayNodeList,
+            0x13 => ItemType::RelationMemberList,
+            0x23 => ItemType::RelationMemberListWithFullMembers,
+            0x40 => ItemType::OuterRing,
+            0x41 => ItemType::InnerRing,
+            0x80 => ItemType::ChangesetDiscussion,
+            _ => return Err(value),
+        })
+    }
+}
+
 /// Memory size of an item.
 ///
 /// Libosmium stores an item's collections continuously in one block of memory
@@ -0,0 +1,130 @@
+//! defines the [ItemStash] to store items which are removed again in any order.
+
+use crate::buffer::ItemBuffer;
+use crate::item::Item;
+
+/// Storage for items which can be removed individually
+///
+/// This mirrors libosmium's `osmium::ItemStash`:
+/// Items are referred to by handles, which stay valid until the item is removed.
+/// Removed items are only forgotten at first and the buffer is compacted,
+/// once they take up more than half of it.
+#[derive(Debug, Default)]
+pub(crate) struct ItemStash {
+    buffer: ItemBuffer,
+
+    /// Every handle's offset in `buffer` or `None` if the handle is free
+    offsets: Vec<Option<usize>>,
+
+    /// Handles whose items have been removed to be reused
+    free: Vec<usize>,
+
+    /// Number of bytes in `buffer` taken by removed items
+    removed_bytes: usize,
+}
+
+impl ItemStash {
+    /// Create an empty stash.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copy an item into the stash and return its handle.
+    pub fn add(&mut self, item: &impl AsRef<Item>) -> usize {
+        let offset = self.buffer.as_bytes().len();
+        self.buffer.push(item);
+        match self.free.pop() {
+            Some(handle) => {
+                self.offsets[handle] = Some(offset);
+                handle
+            }
+            None => {
+                self.offsets.push(Some(offset));
+                self.offsets.len() - 1
+            }
+        }
+    }
+
+    /// Get the item of a handle.
+    ///
+    /// Panics if the item has been removed.
+    pub fn get(&self, handle: usize) -> &Item {
+        let offset = self.offsets[handle].expect("The item has been removed");
+        unsafe { self.buffer.get_unchecked(offset) }
+    }
+
+    /// Remove the item of a handle, which may be reused afterward.
+    pub fn remove(&mut self, handle: usize) {
+        let size = self.get(handle).aligned_size();
+        self.offsets[handle] = None;
+        self.free.push(handle);
+        self.removed_bytes += size;
+
+        if self.removed_bytes * 2 > self.buffer.as_bytes().len() {
+            self.compact();
+        }
+    }
+
+    /// Remove all items.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.offsets.clear();
+        self.free.clear();
+        self.removed_bytes = 0;
+    }
+
+    /// Copy the remaining items into a new buffer
+    fn compact(&mut self) {
+        let mut buffer =
+            ItemBuffer::with_capacity(self.buffer.as_bytes().len() - self.removed_bytes);
+        for offset in self.offsets.iter_mut().flatten() {
+            let item = unsafe { self.buffer.get_unchecked(*offset) };
+            *offset = buffer.as_bytes().len();
+            buffer.push(item);
+        }
+        self.buffer = buffer;
+        self.removed_bytes = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::buffer::tests::node;
+
+    #[test]
+    fn remove_and_compact() {
+        let nodes: Vec<ItemBuffer> = (1..=4)
+            .map(|id| ItemBuffer::from_bytes(&node(id, b"")).unwrap())
+            .collect();
+        let item = |index: usize| nodes[index].iter().next().unwrap();
+
+        let mut stash = ItemStash::new();
+        let handles: Vec<usize> = (0..3).map(|index| stash.add(item(index))).collect();
+        assert_eq!(handles, [0, 1, 2]);
+
+        // The second removal compacts the buffer
+        stash.remove(handles[0]);
+        assert_eq!(stash.removed_bytes, item(0).aligned_size());
+        stash.remove(handles[1]);
+        assert_eq!(stash.removed_bytes, 0);
+        assert_eq!(stash.buffer.as_bytes(), item(2).as_bytes());
+        assert_eq!(stash.get(handles[2]).as_bytes(), item(2).as_bytes());
+
+        // Free handles are reused
+        let handle = stash.add(item(3));
+        assert!(handle < 2);
+        assert_eq!(stash.get(handle).as_bytes(), item(3).as_bytes());
+        assert_eq!(stash.get(handles[2]).as_bytes(), item(2).as_bytes());
+    }
+
+    #[test]
+    #[should_panic(expected = "The item has been removed")]
+    fn get_removed() {
+        let node = ItemBuffer::from_bytes(&node(1, b"")).unwrap();
+        let mut stash = ItemStash::new();
+        let handle = stash.add(node.iter().next().unwrap());
+        stash.remove(handle);
+        stash.get(handle);
+    }
+}
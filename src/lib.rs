@@ -8,14 +8,34 @@
 mod area;
 pub use area::Area;
 
+pub mod area_assembler;
+
+pub mod area_lookup;
+
+pub mod boundary;
+
+pub mod change;
+
+pub mod diff;
+
+pub mod extract;
+
+mod geometry;
+
 pub mod handler;
 pub use handler::Handler;
 
+pub mod id_set;
+
+pub mod io;
+
 mod item;
-pub use item::{Item, ItemRef, ItemType};
+pub use item::{Item, ItemRef, ItemRefMut, ItemSubclass, ItemType};
+
+mod item_stash;
 
 mod location;
-pub use location::{Location, PRECISION};
+pub use location::{BBox, Location, PRECISION};
 
 mod node;
 pub use node::{Node, NodeRef};
@@ -25,13 +45,24 @@ pub mod node_ref_list;
 mod object;
 pub use object::OSMObject;
 
+pub mod object_order;
+
+pub mod parallel;
+
+pub mod relation;
+pub use relation::{Relation, RelationMember};
+
+pub mod relations_manager;
+
+pub mod rtree;
+
 pub mod tag_list;
 
 mod way;
 pub use way::Way;
 
 mod buffer;
-pub use buffer::ItemBuffer;
+pub use buffer::{InvalidItem, ItemBuffer, TypedBuffer};
 
 mod impl_subclass {
     macro_rules! impl_as_ref {
@@ -75,16 +106,39 @@ mod impl_subclass {
 
             impl_as_ref!($class as $crate::object::OSMObject);
             impl_as_ref!($class as $crate::item::Item);
-        }
+        };
+
+        ($class:path as NodeRefList) => {
+            impl std::ops::Deref for $class {
+                type Target = $crate::node_ref_list::NodeRefList;
+
+                #[doc = concat!("Cast to a [", stringify!($crate::node_ref_list::NodeRefList) ,"] reference")]
+                fn deref(&self) -> &Self::Target {
+                    unsafe { std::mem::transmute(self) }
+                }
+            }
+
+            impl std::ops::DerefMut for $class {
+                #[doc = concat!("Cast to a [", stringify!($crate::node_ref_list::NodeRefList) ,"] reference")]
+                fn deref_mut(&mut self) -> &mut Self::Target {
+                    unsafe { std::mem::transmute(self) }
+                }
+            }
+
+            impl_as_ref!($class as $crate::node_ref_list::NodeRefList);
+            impl_as_ref!($class as $crate::item::Item);
+        };
     }
     impl_subclass!(crate::area::Area as OSMObject);
     impl_subclass!(crate::node::Node as OSMObject);
-    impl_subclass!(crate::handler::Relation as OSMObject);
+    impl_subclass!(crate::relation::Relation as OSMObject);
     impl_subclass!(crate::way::Way as OSMObject);
     impl_subclass!(crate::tag_list::TagList as Item);
     impl_subclass!(crate::handler::ChangesetDiscussion as Item);
-    impl_subclass!(crate::handler::RelationMemberList as Item);
+    impl_subclass!(crate::relation::RelationMemberList as Item);
     impl_subclass!(crate::node_ref_list::NodeRefList as Item);
+    impl_subclass!(crate::node_ref_list::OuterRing as NodeRefList);
+    impl_subclass!(crate::node_ref_list::InnerRing as NodeRefList);
     impl_subclass!(crate::handler::Changeset as Item);
     impl_subclass!(crate::object::OSMObject as Item);
 }
@@ -1,5 +1,11 @@
 #include <string.h>
 
+#include <memory>
+#include <queue>
+#include <stdexcept>
+#include <tuple>
+#include <vector>
+
 // Construct areas from relations and ways
 #include "osmium/area/assembler.hpp"
 #include "osmium/area/multipolygon_manager.hpp"
@@ -14,6 +20,15 @@
 // Support reading of pbf files
 #include "osmium/io/pbf_input.hpp"
 
+// Support reading of osm and osc (xml) files
+#include "osmium/io/xml_input.hpp"
+
+// Support reading of gzip compressed files
+#include "osmium/io/gzip_compression.hpp"
+
+// Iterate over a reader's objects when merging files
+#include "osmium/io/input_iterator.hpp"
+
 // Iterators for a TagList's tags and an Area's rings
 #include "osmium/memory/collection.hpp"
 #include "osmium/memory/item_iterator.hpp"
@@ -31,6 +46,12 @@
 // OSMObject class
 #include "osmium/osm/object.hpp"
 
+// Comparison functors for sorting objects
+#include "osmium/osm/object_comparisons.hpp"
+
+// Relation class and its members
+#include "osmium/osm/relation.hpp"
+
 // Way class
 #include "osmium/osm/way.hpp"
 
@@ -41,6 +62,10 @@
 #include "osmium/visitor.hpp"
 
 
+// buffer.rs
+// Callback receiving a buffer's committed memory, returning false to stop
+using BufferCallback = bool (void *, const unsigned char *, size_t);
+
 // area.rs
 extern "C" {
     struct NumRings {
@@ -64,6 +89,54 @@ extern "C" {
     }
 }
 
+// area_assembler.rs
+extern "C" {
+    bool area_assembler_way(const osmium::area::AssemblerConfig &config, const osmium::Way &way, void *context, BufferCallback *callback, char *error_buffer) {
+        try {
+            osmium::area::Assembler assembler{config};
+            osmium::memory::Buffer buffer{1024, osmium::memory::Buffer::auto_grow::yes};
+            const bool success = assembler(way, buffer);
+            callback(context, buffer.data(), buffer.committed());
+            return success;
+        } catch (std::exception& e) {
+            strncpy(error_buffer, e.what(), 255);
+            return false;
+        }
+    }
+
+    bool area_assembler_relation(const osmium::area::AssemblerConfig &config, const osmium::Relation &relation, const osmium::Way *const *ways, size_t num_ways, void *context, BufferCallback *callback, char *error_buffer) {
+        try {
+            // The assembler pairs every member with a non-zero ref with the next way.
+            // Like osmium::relations::RelationsManager, zero the refs of the members which aren't ways.
+            osmium::memory::Buffer relation_buffer{1024, osmium::memory::Buffer::auto_grow::yes};
+            relation_buffer.add_item(relation);
+            relation_buffer.commit();
+            osmium::Relation &copy = relation_buffer.get<osmium::Relation>(0);
+            size_t way_members = 0;
+            for (auto &member : copy.members()) {
+                if (member.type() != osmium::item_type::way) {
+                    member.set_ref(0);
+                } else if (member.ref() != 0) {
+                    way_members++;
+                }
+            }
+            if (way_members != num_ways) {
+                throw std::invalid_argument{"number of ways doesn't match the relation's way members"};
+            }
+
+            osmium::area::Assembler assembler{config};
+            osmium::memory::Buffer buffer{1024, osmium::memory::Buffer::auto_grow::yes};
+            const std::vector<const osmium::Way*> members{ways, ways + num_ways};
+            const bool success = assembler(copy, members, buffer);
+            callback(context, buffer.data(), buffer.committed());
+            return success;
+        } catch (std::exception& e) {
+            strncpy(error_buffer, e.what(), 255);
+            return false;
+        }
+    }
+}
+
 // node.rs
 extern "C" {
     osmium::Location node_location(const osmium::Node &node) {
@@ -99,10 +172,47 @@ extern "C" {
     OSMObject(osmium::user_id_type, uid)
     OSMObject(bool, user_is_anonymous)
     OSMObject(osmium::Timestamp, timestamp)
+    OSMObject(osmium::changeset_id_type, changeset)
     OSMObject(const char *, user)
     OSMObject(const osmium::TagList &, tags)
 }
 
+// object_order.rs
+extern "C" {
+    bool object_equal_type_id_version(const osmium::OSMObject &lhs, const osmium::OSMObject &rhs) {
+        return osmium::object_equal_type_id_version{}(lhs, rhs);
+    }
+    bool object_equal_type_id(const osmium::OSMObject &lhs, const osmium::OSMObject &rhs) {
+        return osmium::object_equal_type_id{}(lhs, rhs);
+    }
+}
+
+// relation.rs
+using relation_member_iterator = osmium::memory::CollectionIterator<const osmium::RelationMember>;
+extern "C" {
+    const osmium::RelationMemberList &relation_members(const osmium::Relation &relation) {
+        return relation.members();
+    }
+    relation_member_iterator relation_member_list_begin(const osmium::RelationMemberList &list) {
+        return list.begin();
+    }
+    relation_member_iterator relation_member_list_end(const osmium::RelationMemberList &list) {
+        return list.end();
+    }
+    void relation_member_iterator_increment(relation_member_iterator &iter) {
+        iter++;
+    }
+    osmium::object_id_type relation_member_ref(const osmium::RelationMember &member) {
+        return member.ref();
+    }
+    osmium::item_type relation_member_type(const osmium::RelationMember &member) {
+        return member.type();
+    }
+    const char *relation_member_role(const osmium::RelationMember &member) {
+        return member.role();
+    }
+}
+
 // tag_list.rs
 extern "C" {
     osmium::memory::CollectionIterator<const osmium::Tag> tag_list_begin(const osmium::TagList &list) {
@@ -124,11 +234,38 @@ extern "C" {
     }
 }
 
+// io.rs
+
+struct FileSpec {
+    const char *path;
+    const char *format;
+    const char *buffer;
+    size_t buffer_size;
+};
+
+// Create the file described by rust's FileSpec reading from its buffer, if it has one
+osmium::io::File make_file(const FileSpec &spec) {
+    if (spec.buffer != nullptr) {
+        return osmium::io::File{spec.buffer, spec.buffer_size, spec.format};
+    }
+    return osmium::io::File{spec.path, spec.format};
+}
+
+struct Files {
+    const FileSpec *specs;
+    size_t count;
+    bool merge;
+};
+
 // handler.rs
 
 template <class T>
 using HandlerFunc = void (void *, const T &);
 
+struct Progress {
+    uint64_t offset, file_size;
+};
+
 class RustHandler : public osmium::handler::Handler {
 private:
     void *self_pointer;
@@ -145,6 +282,7 @@ private:
     HandlerFunc<osmium::Way>*                 way_handler;
     HandlerFunc<osmium::WayNodeList>*         way_node_list_handler;
     void (*flush_handler)(void *);
+    HandlerFunc<Progress>*                    progress_handler;
 
 public:
     void area                 (const osmium::Area&                arg) { area_handler                 (self_pointer, arg); }
@@ -160,70 +298,254 @@ public:
     void way                  (const osmium::Way&                 arg) { way_handler                  (self_pointer, arg); }
     void way_node_list        (const osmium::WayNodeList&         arg) { way_node_list_handler        (self_pointer, arg); }
     void flush() { flush_handler(self_pointer); }
+    void progress(uint64_t offset, uint64_t file_size) { progress_handler(self_pointer, {.offset = offset, .file_size = file_size}); }
 };
 
 using way_creator_map = osmium::index::map::FlexMem<osmium::unsigned_object_id_type, osmium::Location>;
 using way_creator_type = osmium::handler::NodeLocationsForWays<way_creator_map>;
 using area_creator_type = osmium::area::MultipolygonManager<osmium::area::Assembler>;
 
-extern "C" {
-    void apply(RustHandler handler, const char *path, char *error_buffer) {
+// Assemble areas from closed ways only, like osmium::area::MultipolygonManager does for its ways,
+// and pass them on to the RustHandler right away
+class WayAreaHandler : public osmium::handler::Handler {
+private:
+    osmium::area::AssemblerConfig config;
+    RustHandler &handler;
+    osmium::memory::Buffer buffer{1024, osmium::memory::Buffer::auto_grow::yes};
+
+public:
+    WayAreaHandler(const osmium::area::AssemblerConfig &config, RustHandler &handler) : config(config), handler(handler) {}
+
+    // Mirrors osmium::area::MultipolygonManager::way_not_in_any_relation
+    void way(const osmium::Way &way) {
+        // Ignore ways which can't form a ring
+        if (way.nodes().size() <= 3) {
+            return;
+        }
+        if (!way.nodes().front().location() || !way.nodes().back().location() || !way.ends_have_same_location()) {
+            return;
+        }
+        // Ignore ways without tags or explicitly tagged as not being areas
+        if (way.tags().empty() || way.tags().has_tag("area", "no")) {
+            return;
+        }
+
+        // Like libosmium, skip ways whose other nodes have invalid locations
         try {
-            osmium::io::File file{path};
+            osmium::area::Assembler assembler{config};
+            assembler(way, buffer);
+        } catch (const osmium::invalid_location &) {
+            buffer.clear();
+            return;
+        }
+        for (auto &item : buffer) {
+            osmium::apply_item(item, handler);
+        }
+        buffer.clear();
+    }
+};
 
-            osmium::io::Reader reader{file};
-            osmium::apply(
-                reader,
-                handler
-            );
+// Apply handlers to all items in a buffer without flushing them (unlike osmium::apply)
+template <typename TBuffer, typename... THandlers>
+void apply_buffer(TBuffer &buffer, THandlers&... handlers) {
+    for (auto &item : buffer) {
+        osmium::apply_item(item, handlers...);
+    }
+}
+
+// Flush all handlers in order
+template <typename... THandlers>
+void flush(THandlers&... handlers) {
+    (void)std::initializer_list<int>{(handlers.flush(), 0)...};
+}
+
+// Like osmium::apply, but reports the reader's progress to the RustHandler after every buffer
+//
+// `pass` and `passes` map the file's offset into the progress of a multi pass process.
+template <typename... THandlers>
+void apply_reader(osmium::io::Reader &reader, RustHandler &handler, int pass, int passes, THandlers&... handlers) {
+    const uint64_t file_size = reader.file_size();
+    while (osmium::memory::Buffer buffer = reader.read()) {
+        apply_buffer(buffer, handlers...);
+        handler.progress(pass * file_size + reader.offset(), passes * file_size);
+    }
+}
+
+// Merge sort the objects of several sorted readers like `osmium merge` does,
+// skipping objects whose type, id and version have already been seen.
+template <typename... THandlers>
+void apply_merged(std::vector<std::unique_ptr<osmium::io::Reader>> &readers, RustHandler &handler, int pass, int passes, THandlers&... handlers) {
+    using iterator = osmium::io::InputIterator<osmium::io::Reader, osmium::OSMObject>;
+    struct Source {
+        iterator current, end;
+    };
+
+    uint64_t file_size = 0;
+    std::vector<Source> sources;
+    for (auto &reader : readers) {
+        file_size += reader->file_size();
+        sources.push_back({iterator{*reader}, iterator{}});
+    }
+
+    // Orders the queue's source indices by their current objects, putting the smallest on top.
+    // Equal objects are taken from the earliest source first.
+    auto greater = [&sources](size_t lhs, size_t rhs) {
+        const osmium::object_order_type_id_version less{};
+        const osmium::OSMObject &lhs_object = *sources[lhs].current;
+        const osmium::OSMObject &rhs_object = *sources[rhs].current;
+        if (less(rhs_object, lhs_object)) return true;
+        if (less(lhs_object, rhs_object)) return false;
+        return lhs > rhs;
+    };
+    std::priority_queue<size_t, std::vector<size_t>, decltype(greater)> queue{greater};
+    for (size_t index = 0; index < sources.size(); index++) {
+        if (sources[index].current != sources[index].end) {
+            queue.push(index);
+        }
+    }
+
+    // Only the previous object's key is stored, because its buffer might be freed already
+    bool has_previous = false;
+    std::tuple<osmium::item_type, osmium::object_id_type, osmium::object_version_type> previous;
+    uint64_t reported_offset = 0;
+    while (!queue.empty()) {
+        const size_t index = queue.top();
+        queue.pop();
+
+        Source &source = sources[index];
+        osmium::OSMObject &object = *source.current;
+        const auto key = std::make_tuple(object.type(), object.id(), object.version());
+        if (!has_previous || key != previous) {
+            osmium::apply_item(object, handlers...);
+            has_previous = true;
+            previous = key;
+        }
+
+        ++source.current;
+        if (source.current != source.end) {
+            queue.push(index);
+        }
+
+        // The offsets only change, when a reader returned a new buffer
+        uint64_t offset = 0;
+        for (auto &reader : readers) {
+            offset += reader->offset();
+        }
+        if (offset != reported_offset) {
+            handler.progress(pass * file_size + offset, passes * file_size);
+            reported_offset = offset;
+        }
+    }
+}
+
+// Read several files one after another or merged, applying handlers on every item
+//
+// Like in apply_reader, `pass` and `passes` map the progress into a multi pass process.
+// When reading the files one after another, every file is assumed to take an equal share.
+template <typename... THandlers>
+void apply_files(const Files &files, osmium::osm_entity_bits::type entities, osmium::io::read_meta meta, RustHandler &handler, int pass, int passes, THandlers&... handlers) {
+    if (files.merge) {
+        std::vector<std::unique_ptr<osmium::io::Reader>> readers;
+        for (size_t index = 0; index < files.count; index++) {
+            readers.emplace_back(new osmium::io::Reader{make_file(files.specs[index]), entities, meta});
+        }
+        apply_merged(readers, handler, pass, passes, handlers...);
+        for (auto &reader : readers) {
+            reader->close();
+        }
+    } else {
+        for (size_t index = 0; index < files.count; index++) {
+            osmium::io::Reader reader{make_file(files.specs[index]), entities, meta};
+            apply_reader(reader, handler, pass * files.count + index, passes * files.count, handlers...);
             reader.close();
+        }
+    }
+}
+
+extern "C" {
+    void apply(RustHandler handler, const Files &files, char *error_buffer) {
+        try {
+            apply_files(files, osmium::osm_entity_bits::all, osmium::io::read_meta::yes, handler, 0, 1, handler);
+            flush(handler);
         } catch (std::exception& e) {
             strncpy(error_buffer, e.what(), 255);
         }
     }
 
-    void apply_with_ways(RustHandler handler, const char *path, char *error_buffer) {
+    void apply_relations(RustHandler handler, const Files &files, char *error_buffer) {
         try {
-            osmium::io::File file{path};
+            // Equivalent to osmium::relations::read_relations
+            apply_files(files, osmium::osm_entity_bits::relation, osmium::io::read_meta::yes, handler, 0, 1, handler);
+            flush(handler);
+        } catch (std::exception& e) {
+            strncpy(error_buffer, e.what(), 255);
+        }
+    }
 
+    void apply_with_ways(RustHandler handler, const Files &files, char *error_buffer) {
+        try {
             way_creator_map map;
             way_creator_type way_creator{map};
             way_creator.ignore_errors();
 
-            osmium::io::Reader reader{file};
-            osmium::apply(
-                reader,
-                way_creator,
-                handler
-            );
-            reader.close();
+            apply_files(files, osmium::osm_entity_bits::all, osmium::io::read_meta::yes, handler, 0, 1, way_creator, handler);
+            flush(way_creator, handler);
         } catch (std::exception& e) {
             strncpy(error_buffer, e.what(), 255);
         }
     }
 
-    void apply_with_areas(RustHandler handler, const char *path, char *error_buffer, osmium::area::AssemblerConfig config) {
+    void apply_with_areas(RustHandler handler, const Files &files, char *error_buffer, osmium::area::AssemblerConfig config) {
         try {
-            const osmium::io::File file{path};
-
             way_creator_map map;
             way_creator_type way_creator{map};
             way_creator.ignore_errors();
 
+            // First pass equivalent to osmium::relations::read_relations
             area_creator_type area_creator{config};
-            osmium::relations::read_relations(file, area_creator);
-
-            osmium::io::Reader reader{file, osmium::io::read_meta::no};
-            osmium::apply(
-                reader,
-                way_creator,
-                handler,
-                area_creator.handler(
-                    [&handler](const osmium::memory::Buffer &area_buffer) {
-                        osmium::apply(area_buffer, handler);
-                    }
-                )
+            apply_files(files, osmium::osm_entity_bits::relation, osmium::io::read_meta::yes, handler, 0, 2, area_creator);
+            area_creator.prepare_for_lookup();
+
+            auto area_handler = area_creator.handler(
+                [&handler](const osmium::memory::Buffer &area_buffer) {
+                    apply_buffer(area_buffer, handler);
+                }
             );
+            apply_files(files, osmium::osm_entity_bits::all, osmium::io::read_meta::no, handler, 1, 2, way_creator, handler, area_handler);
+            flush(way_creator, area_handler, handler);
+        } catch (std::exception& e) {
+            strncpy(error_buffer, e.what(), 255);
+        }
+    }
+
+    void apply_with_way_areas(RustHandler handler, const Files &files, char *error_buffer, osmium::area::AssemblerConfig config) {
+        try {
+            way_creator_map map;
+            way_creator_type way_creator{map};
+            way_creator.ignore_errors();
+
+            WayAreaHandler area_handler{config, handler};
+            apply_files(files, osmium::osm_entity_bits::all, osmium::io::read_meta::yes, handler, 0, 1, way_creator, handler, area_handler);
+            flush(way_creator, handler);
+        } catch (std::exception& e) {
+            strncpy(error_buffer, e.what(), 255);
+        }
+    }
+}
+
+// parallel.rs
+
+extern "C" {
+    void read_buffers(const FileSpec &spec, void *context, BufferCallback *callback, char *error_buffer) {
+        try {
+            osmium::io::File file = make_file(spec);
+
+            osmium::io::Reader reader{file};
+            while (osmium::memory::Buffer buffer = reader.read()) {
+                if (!callback(context, buffer.data(), buffer.committed())) {
+                    break;
+                }
+            }
             reader.close();
         } catch (std::exception& e) {
             strncpy(error_buffer, e.what(), 255);
@@ -12,7 +12,7 @@ pub const PRECISION: i32 = 10000000;
 ///
 /// [Libosmium's cpp reference](https://docs.osmcode.org/libosmium/latest/classosmium_1_1Location.html)
 #[repr(C)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Location {
     /// Longitude in `1/PRECISION` degrees.
     pub raw_x: i32,
@@ -22,6 +22,16 @@ pub struct Location {
 }
 
 impl Location {
+    /// Create a location from longitude and latitude in degrees.
+    ///
+    /// The degrees are rounded to the nearest `1/PRECISION`-th.
+    pub fn new(lon: f64, lat: f64) -> Self {
+        Location {
+            raw_x: (lon * PRECISION as f64).round() as i32,
+            raw_y: (lat * PRECISION as f64).round() as i32,
+        }
+    }
+
     /// Returns true if at least one of the coordinates is defined.
     pub fn is_defined(&self) -> bool {
         self.raw_x != UNDEFINED_COORDINATE || self.raw_y != UNDEFINED_COORDINATE
@@ -51,6 +61,125 @@ impl Location {
     }
 }
 
+/// A bounding box given by its bottom left and top right corners
+///
+/// A new box is undefined and grows by [extending](BBox::extend) it with locations.
+///
+/// [Libosmium's cpp reference](https://docs.osmcode.org/libosmium/latest/classosmium_1_1Box.html)
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BBox {
+    /// The corner with the smallest longitude and latitude
+    pub bottom_left: Location,
+
+    /// The corner with the largest longitude and latitude
+    pub top_right: Location,
+}
+
+impl BBox {
+    /// Create an undefined box.
+    pub const fn new() -> Self {
+        let undefined = Location {
+            raw_x: UNDEFINED_COORDINATE,
+            raw_y: UNDEFINED_COORDINATE,
+        };
+        BBox {
+            bottom_left: undefined,
+            top_right: undefined,
+        }
+    }
+
+    /// Create a box from its corners' longitudes and latitudes in degrees.
+    pub fn from_degrees(min_lon: f64, min_lat: f64, max_lon: f64, max_lat: f64) -> Self {
+        BBox {
+            bottom_left: Location::new(min_lon, min_lat),
+            top_right: Location::new(max_lon, max_lat),
+        }
+    }
+
+    /// Returns true if both corners are defined.
+    pub fn is_defined(&self) -> bool {
+        self.bottom_left.is_defined() && self.top_right.is_defined()
+    }
+
+    /// Extend the box to include a location.
+    ///
+    /// Undefined locations are ignored.
+    pub fn extend(&mut self, location: Location) -> &mut Self {
+        if location.is_undefined() {
+            return self;
+        }
+        if self.is_defined() {
+            self.bottom_left.raw_x = self.bottom_left.raw_x.min(location.raw_x);
+            self.bottom_left.raw_y = self.bottom_left.raw_y.min(location.raw_y);
+            self.top_right.raw_x = self.top_right.raw_x.max(location.raw_x);
+            self.top_right.raw_y = self.top_right.raw_y.max(location.raw_y);
+        } else {
+            self.bottom_left = location;
+            self.top_right = location;
+        }
+        self
+    }
+
+    /// Check whether a location is inside the box or on its border.
+    pub fn contains(&self, location: Location) -> bool {
+        self.is_defined()
+            && location.is_defined()
+            && self.bottom_left.raw_x <= location.raw_x
+            && location.raw_x <= self.top_right.raw_x
+            && self.bottom_left.raw_y <= location.raw_y
+            && location.raw_y <= self.top_right.raw_y
+    }
+
+    /// Check whether another box is inside this one, including its border.
+    pub fn contains_bbox(&self, other: &BBox) -> bool {
+        self.contains(other.bottom_left) && self.contains(other.top_right)
+    }
+
+    /// Check whether two boxes share at least one point.
+    pub fn intersects(&self, other: &BBox) -> bool {
+        self.is_defined()
+            && other.is_defined()
+            && self.bottom_left.raw_x <= other.top_right.raw_x
+            && other.bottom_left.raw_x <= self.top_right.raw_x
+            && self.bottom_left.raw_y <= other.top_right.raw_y
+            && other.bottom_left.raw_y <= self.top_right.raw_y
+    }
+}
+
+impl Default for BBox {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Location {
+    /// Serialize the location as longitude and latitude in degrees
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("Location", 2)?;
+        state.serialize_field("lon", &self.lon())?;
+        state.serialize_field("lat", &self.lat())?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Location {
+    /// Deserialize a location from longitude and latitude in degrees
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(serde::Deserialize)]
+        #[serde(rename = "Location")]
+        struct Degrees {
+            lon: f64,
+            lat: f64,
+        }
+        let Degrees { lon, lat } = Degrees::deserialize(deserializer)?;
+        Ok(Location::new(lon, lat))
+    }
+}
+
 impl Display for Location {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let (mut x1, mut x2) = (self.raw_x / PRECISION, self.raw_x % PRECISION);
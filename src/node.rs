@@ -1,6 +1,8 @@
 use std::mem::MaybeUninit;
 
 use crate::location::Location;
+#[cfg(feature = "serde")]
+use crate::object::OSMObject;
 
 /// Reference to a [Node]
 ///
@@ -27,6 +29,17 @@ impl NodeRef {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for NodeRef {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("NodeRef", 2)?;
+        state.serialize_field("id", &self.id)?;
+        state.serialize_field("location", &self.get_location())?;
+        state.end()
+    }
+}
+
 /// A Node is some point on the map with associated tags.
 pub enum Node {}
 
@@ -39,12 +52,24 @@ impl Node {
     /// set the node's location
     pub fn set_location(&mut self, location: &Location) {
         unsafe {
-            set_node_location(self, location);
+            node_set_location(self, location);
         }
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Node {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("Node", OSMObject::SERIALIZED_FIELDS + 1)?;
+        self.serialize_fields(&mut state)?;
+        let location = self.location();
+        state.serialize_field("location", &Some(location).filter(Location::is_valid))?;
+        state.end()
+    }
+}
+
 extern "C" {
     fn node_location(node: &Node) -> Location;
-    fn set_node_location(node: &mut Node, location: &Location);
+    fn node_set_location(node: &mut Node, location: &Location);
 }
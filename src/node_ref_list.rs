@@ -5,9 +5,13 @@
 //! a single class attribute as well as a check method depending on it.
 //!
 //! Since this binding doesn't care about libosmium's internal memory layout
-//! and there was no need to implement this check, the subclasses are just type aliases on rust's side.
+//! and there was no need to implement this check, [WayNodeList] is just a type alias on rust's side.
+//! The rings are distinct types which [dereference](Deref) into a [NodeRefList],
+//! so an [InnerRing] can't be mixed up with an [OuterRing] and both can provide ring specific methods.
 use std::ops::{Deref, DerefMut};
 
+use crate::geometry;
+use crate::location::{BBox, Location};
 use crate::NodeRef;
 
 /// A node ref list is a name of [NodeRefs](crate::NodeRef) which are stored in a slice.
@@ -17,10 +21,84 @@ pub enum NodeRefList {}
 pub type WayNodeList = NodeRefList;
 
 /// One of an [Area](crate::Area)'s multipolygon's outer rings' inner rings
-pub type InnerRing = NodeRefList;
+///
+/// Use [`Area::polygons`](crate::Area::polygons) to get the inner rings together with their outer ring.
+pub enum InnerRing {}
 
 /// One of an [Area](crate::Area)'s multipolygon's outer rings
-pub type OuterRing = NodeRefList;
+///
+/// Use [`Area::polygons`](crate::Area::polygons) to get the outer rings together with their inner rings.
+/// An outer ring has no method for its inner rings, because they are stored after it up to the end of the area,
+/// which only the area itself knows.
+pub enum OuterRing {}
+
+impl NodeRefList {
+    /// Check whether the first and last node are the same, by comparing their ids.
+    ///
+    /// An empty list is not closed.
+    pub fn is_closed(&self) -> bool {
+        match (self.first(), self.last()) {
+            (Some(first), Some(last)) => first.id == last.id,
+            _ => false,
+        }
+    }
+
+    /// Check whether the first and last node have the same valid location.
+    pub fn ends_have_same_location(&self) -> bool {
+        match (self.first(), self.last()) {
+            (Some(first), Some(last)) => {
+                first.get_location().is_some() && first.get_location() == last.get_location()
+            }
+            _ => false,
+        }
+    }
+
+    /// Calculate the bounding box of all valid locations.
+    ///
+    /// The box is undefined if there are none.
+    pub fn envelope(&self) -> BBox {
+        let mut envelope = BBox::new();
+        for location in self.iter().filter_map(NodeRef::get_location) {
+            envelope.extend(location);
+        }
+        envelope
+    }
+}
+
+/// Macro implementing the methods shared by [OuterRing] and [InnerRing]
+macro_rules! impl_ring {
+    ($($ring:ident),*) => {$(
+        impl $ring {
+            /// Calculate the ring's area in square degrees using the shoelace formula.
+            ///
+            /// The area is positive for counter-clockwise rings and negative for clockwise ones.
+            /// Nodes with invalid locations are skipped.
+            pub fn signed_area(&self) -> f64 {
+                geometry::signed_area(geometry::locations(self))
+            }
+
+            /// Is the ring oriented clockwise?
+            pub fn is_clockwise(&self) -> bool {
+                self.signed_area() < 0.0
+            }
+
+            /// Is the ring oriented counter-clockwise?
+            pub fn is_counter_clockwise(&self) -> bool {
+                self.signed_area() > 0.0
+            }
+
+            /// Check whether a location is inside the ring.
+            ///
+            /// This uses the even-odd rule and treats the ring as closed, even if it isn't.
+            /// Locations exactly on the ring's border may be considered inside or outside.
+            /// Nodes with invalid locations are skipped.
+            pub fn contains(&self, location: Location) -> bool {
+                geometry::ring_contains(geometry::locations(self), location)
+            }
+        }
+    )*};
+}
+impl_ring!(OuterRing, InnerRing);
 
 impl Deref for NodeRefList {
     type Target = [NodeRef];
@@ -45,6 +123,30 @@ impl DerefMut for NodeRefList {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for NodeRefList {
+    /// Serialize the list as sequence of [NodeRefs](crate::NodeRef)
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.deref().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for OuterRing {
+    /// Serialize the ring as sequence of [NodeRefs](crate::NodeRef)
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.deref().serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for InnerRing {
+    /// Serialize the ring as sequence of [NodeRefs](crate::NodeRef)
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.deref().serialize(serializer)
+    }
+}
+
 extern "C" {
     fn node_ref_list_begin_const(list: &NodeRefList) -> &NodeRef;
     fn node_ref_list_begin(list: &mut NodeRefList) -> &mut NodeRef;
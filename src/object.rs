@@ -1,6 +1,8 @@
 use std::ffi::CStr;
 use std::os::raw::c_char;
 
+#[cfg(feature = "serde")]
+use crate::item::Item;
 use crate::tag_list::TagList;
 
 /// Base class for OSM 's objects:
@@ -54,6 +56,11 @@ impl OSMObject {
         unsafe { OSMObject_timestamp(self) }
     }
 
+    /// Get id of the changeset this object was last changed in.
+    pub fn changeset(&self) -> ChangesetId {
+        unsafe { OSMObject_changeset(self) }
+    }
+
     /// Get user name for this object.
     pub fn user(&self) -> &CStr {
         unsafe { CStr::from_ptr(OSMObject_user(self)) }
@@ -65,6 +72,36 @@ impl OSMObject {
     }
 }
 
+#[cfg(feature = "serde")]
+impl OSMObject {
+    /// Number of fields [`serialize_fields`](OSMObject::serialize_fields) writes
+    pub(crate) const SERIALIZED_FIELDS: usize = 9;
+
+    /// Write the metadata and tags shared by all subclasses into a subclass' struct
+    pub(crate) fn serialize_fields<S: serde::ser::SerializeStruct>(
+        &self,
+        state: &mut S,
+    ) -> Result<(), S::Error> {
+        state.serialize_field("type", &AsRef::<Item>::as_ref(self).item_type())?;
+        state.serialize_field("id", &self.id())?;
+        state.serialize_field("version", &self.version())?;
+        state.serialize_field("visible", &self.visible())?;
+        state.serialize_field("changeset", &self.changeset())?;
+        state.serialize_field("timestamp", &self.timestamp())?;
+        state.serialize_field("uid", &self.uid())?;
+        state.serialize_field("user", &self.user().to_string_lossy())?;
+        state.serialize_field("tags", self.tags())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for OSMObject {
+    /// Serialize the object as its actual subclass
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        AsRef::<Item>::as_ref(self).serialize(serializer)
+    }
+}
+
 /// Type for OSM user IDs.
 pub type UserId = u32;
 
@@ -74,6 +111,9 @@ pub type ObjectId = i64;
 /// Type for OSM object (node, way, or relation) IDs where we only allow positive IDs.
 pub type UnsignedObjectId = u64;
 
+/// Type for OSM changeset IDs.
+pub type ChangesetId = u32;
+
 /// Type for OSM object version number.
 pub type ObjectVersion = u32;
 
@@ -92,6 +132,7 @@ extern "C" {
     fn OSMObject_uid(object: &OSMObject) -> UserId;
     fn OSMObject_user_is_anonymous(object: &OSMObject) -> bool;
     fn OSMObject_timestamp(object: &OSMObject) -> Timestamp;
+    fn OSMObject_changeset(object: &OSMObject) -> ChangesetId;
     fn OSMObject_user(object: &OSMObject) -> *const c_char;
     fn OSMObject_tags(object: &OSMObject) -> &TagList;
 }
@@ -0,0 +1,214 @@
+//! defines orderings on [OSMObjects](OSMObject) and the [ObjectPointerCollection] to sort objects with.
+//!
+//! The comparison functions mirror libosmium's functors from `<osmium/osm/object_comparisons.hpp>`.
+//! The orderings are implemented in Rust, while the equality checks call libosmium.
+//! The collection mirrors `osmium::ObjectPointerCollection`.
+//!
+//! The canonical order of osm files is [`object_order_type_id_version`]:
+//! first all nodes, then all ways and finally all relations, each sorted by id and version.
+//! Negative ids are put before positive ones.
+//!
+//! Unlike libosmium's functors, which consider a missing timestamp equal to any other timestamp,
+//! the orderings compare missing timestamps as earlier than all others.
+//! This makes them total orders, as required by Rust's sorting functions.
+
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use crate::buffer::ItemBuffer;
+use crate::item::{Item, ItemType};
+use crate::object::{OSMObject, UnsignedObjectId};
+
+/// Order objects by type, id, version and timestamp.
+///
+/// This is the canonical order of osm files.
+pub fn object_order_type_id_version(lhs: &OSMObject, rhs: &OSMObject) -> Ordering {
+    // A missing timestamp is 0, so it is earlier than all others
+    let key = |object: &OSMObject| (type_id(object), object.version(), object.timestamp());
+    key(lhs).cmp(&key(rhs))
+}
+
+/// Order objects by type and id, but put the highest version (and latest timestamp) first.
+pub fn object_order_type_id_reverse_version(lhs: &OSMObject, rhs: &OSMObject) -> Ordering {
+    let key = |object: &OSMObject| {
+        (
+            type_id(object),
+            Reverse(object.version()),
+            Reverse(object.timestamp()),
+        )
+    };
+    key(lhs).cmp(&key(rhs))
+}
+
+/// Check whether two objects have the same type, id and version.
+pub fn object_equal_type_id_version(lhs: &OSMObject, rhs: &OSMObject) -> bool {
+    unsafe { object_equal_type_id_version_ffi(lhs, rhs) }
+}
+
+/// Check whether two objects have the same type and id.
+pub fn object_equal_type_id(lhs: &OSMObject, rhs: &OSMObject) -> bool {
+    unsafe { object_equal_type_id_ffi(lhs, rhs) }
+}
+
+/// Get the key of an object's type and id, putting negative ids before positive ones
+fn type_id(object: &OSMObject) -> (ItemType, bool, UnsignedObjectId) {
+    (
+        AsRef::<Item>::as_ref(object).item_type(),
+        object.id() > 0,
+        object.positive_id(),
+    )
+}
+
+/// A collection of references to [OSMObjects](OSMObject) which can be sorted and deduplicated
+/// without moving the objects themselves.
+///
+/// Use [`into_buffer`](ObjectPointerCollection::into_buffer) to copy the objects in their new order.
+#[derive(Clone, Default)]
+pub struct ObjectPointerCollection<'a> {
+    objects: Vec<&'a OSMObject>,
+}
+
+impl<'a> ObjectPointerCollection<'a> {
+    /// Construct a new, empty collection.
+    pub const fn new() -> Self {
+        Self {
+            objects: Vec::new(),
+        }
+    }
+
+    /// Add an object to the end of the collection.
+    pub fn push(&mut self, object: &'a OSMObject) {
+        self.objects.push(object);
+    }
+
+    /// Sort the objects in the [canonical order](object_order_type_id_version).
+    pub fn sort(&mut self) {
+        self.sort_by(object_order_type_id_version);
+    }
+
+    /// Sort the objects using a comparison function.
+    ///
+    /// The sort is stable i.e. equal objects are not reordered.
+    /// Like for [`slice::sort_by`] the function has to be a total order.
+    pub fn sort_by(&mut self, mut compare: impl FnMut(&OSMObject, &OSMObject) -> Ordering) {
+        self.objects.sort_by(|lhs, rhs| compare(lhs, rhs));
+    }
+
+    /// Remove consecutive objects with the [same type, id and version](object_equal_type_id_version).
+    ///
+    /// Only the first of the duplicates is kept.
+    pub fn unique(&mut self) {
+        self.unique_by(object_equal_type_id_version);
+    }
+
+    /// Remove consecutive objects which are considered equal by a function.
+    ///
+    /// Only the first of the duplicates is kept.
+    pub fn unique_by(&mut self, mut equal: impl FnMut(&OSMObject, &OSMObject) -> bool) {
+        self.objects
+            .dedup_by(|current, previous| equal(previous, current));
+    }
+
+    /// Returns an iterator over the objects.
+    pub fn iter(&self) -> impl Iterator<Item = &'a OSMObject> + '_ {
+        self.objects.iter().copied()
+    }
+
+    /// Returns the number of objects.
+    pub fn len(&self) -> usize {
+        self.objects.len()
+    }
+
+    /// Return true if the collection contains no objects.
+    pub fn is_empty(&self) -> bool {
+        self.objects.is_empty()
+    }
+
+    /// Remove all objects from the collection.
+    pub fn clear(&mut self) {
+        self.objects.clear();
+    }
+
+    /// Copy the objects into a new buffer in the collection's order.
+    pub fn into_buffer(self) -> ItemBuffer {
+        let mut buffer = ItemBuffer::new();
+        for object in self.objects {
+            buffer.push(object);
+        }
+        buffer
+    }
+}
+
+impl<'a> FromIterator<&'a OSMObject> for ObjectPointerCollection<'a> {
+    fn from_iter<T: IntoIterator<Item = &'a OSMObject>>(iter: T) -> Self {
+        Self {
+            objects: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl<'a> Extend<&'a OSMObject> for ObjectPointerCollection<'a> {
+    fn extend<T: IntoIterator<Item = &'a OSMObject>>(&mut self, iter: T) {
+        self.objects.extend(iter);
+    }
+}
+
+impl<'a> From<&'a ItemBuffer> for ObjectPointerCollection<'a> {
+    /// Collect all osm objects from a buffer
+    fn from(buffer: &'a ItemBuffer) -> Self {
+        buffer.select::<OSMObject>().collect()
+    }
+}
+
+/// Implementation of [`ItemBuffer::merge`]
+pub(crate) fn merge<'a>(buffers: impl IntoIterator<Item = &'a ItemBuffer>) -> ItemBuffer {
+    /// An object in the heap together with the index of the buffer it came from
+    struct Head<'a>(&'a OSMObject, usize);
+    impl PartialEq for Head<'_> {
+        fn eq(&self, other: &Self) -> bool {
+            self.cmp(other) == Ordering::Equal
+        }
+    }
+    impl Eq for Head<'_> {}
+    impl PartialOrd for Head<'_> {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+    impl Ord for Head<'_> {
+        /// Reversed to turn the max-heap into a min-heap
+        fn cmp(&self, other: &Self) -> Ordering {
+            object_order_type_id_version(other.0, self.0).then(other.1.cmp(&self.1))
+        }
+    }
+
+    let mut sources: Vec<_> = buffers
+        .into_iter()
+        .map(|buffer| buffer.select::<OSMObject>())
+        .collect();
+    let mut heap: BinaryHeap<Head> = sources
+        .iter_mut()
+        .enumerate()
+        .filter_map(|(index, source)| Some(Head(source.next()?, index)))
+        .collect();
+
+    let mut merged = ItemBuffer::new();
+    let mut previous: Option<&OSMObject> = None;
+    while let Some(Head(object, index)) = heap.pop() {
+        if !previous.is_some_and(|previous| object_equal_type_id_version(previous, object)) {
+            merged.push(object);
+            previous = Some(object);
+        }
+        if let Some(next) = sources[index].next() {
+            heap.push(Head(next, index));
+        }
+    }
+    merged
+}
+
+extern "C" {
+    #[link_name = "object_equal_type_id_version"]
+    fn object_equal_type_id_version_ffi(lhs: &OSMObject, rhs: &OSMObject) -> bool;
+    #[link_name = "object_equal_type_id"]
+    fn object_equal_type_id_ffi(lhs: &OSMObject, rhs: &OSMObject) -> bool;
+}
@@ -0,0 +1,99 @@
+//! defines [`apply`] which processes a file on multiple threads.
+//!
+//! libosmium already decodes PBF blocks in parallel, but [`Handler::apply`] calls the handler
+//! from a single thread. When the handler itself is the bottleneck, its work can be spread
+//! across multiple threads by processing each decoded buffer with one of many handler clones.
+
+use std::ffi::c_void;
+use std::num::NonZeroUsize;
+use std::os::raw::c_char;
+use std::sync::mpsc::{sync_channel, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::buffer::{BufferCallback, ItemBuffer};
+use crate::handler::Handler;
+use crate::io::{Error, File, FileSpec};
+
+/// Read a file and process its items using multiple clones of a handler on multiple threads.
+///
+/// The file is read in buffers of items which are distributed among `threads` workers.
+/// Each worker owns a clone of `handler` (the original is used by one of them)
+/// and processes every item of its buffers using [`apply_item`](Handler::apply_item).
+/// After all buffers are processed, every worker [flushes](Handler::flush) its handler.
+///
+/// Finally, the workers' handlers are merged into a single one using `reduce`, which is returned.
+///
+/// Note that items are not processed in file order and a single handler only sees parts of the file.
+/// For example a way's nodes will in general have been processed by different workers.
+///
+/// # Panics
+///
+/// If any handler panics, the panic is propagated after all workers stopped.
+pub fn apply<H, R>(
+    file: File<'_>,
+    handler: H,
+    threads: NonZeroUsize,
+    mut reduce: R,
+) -> Result<H, Error>
+where
+    H: Handler + Clone + Send,
+    R: FnMut(H, H) -> H,
+{
+    let mut handlers = vec![handler; threads.get()];
+    let (sender, receiver) = sync_channel::<ItemBuffer>(threads.get() * 2);
+    // Only the workers own the receiver, so sending fails once all of them stopped.
+    let receiver = Arc::new(Mutex::new(receiver));
+
+    let result = thread::scope(|scope| {
+        for handler in handlers.iter_mut() {
+            let receiver = receiver.clone();
+            scope.spawn(move || {
+                loop {
+                    // Release the lock before processing the buffer
+                    let buffer = receiver.lock().map(|receiver| receiver.recv());
+                    let Ok(Ok(buffer)) = buffer else {
+                        break;
+                    };
+                    for item in &buffer {
+                        handler.apply_item(item);
+                    }
+                }
+                handler.flush();
+            });
+        }
+
+        drop(receiver);
+
+        let result = file.read(|file, error| unsafe {
+            read_buffers(file, &sender as *const _ as *mut c_void, send_buffer, error)
+        });
+        drop(sender);
+        result
+    });
+    result?;
+
+    Ok(handlers
+        .into_iter()
+        .reduce(&mut reduce)
+        .expect("There is at least one thread"))
+}
+
+/// Callback passed to `read_buffers` which copies a buffer and sends it to the workers
+///
+/// Returns `false` to stop reading when all workers have stopped.
+extern "C" fn send_buffer(context: *mut c_void, data: *const u8, size: usize) -> bool {
+    let sender = unsafe { &*(context as *const SyncSender<ItemBuffer>) };
+    let buffer =
+        unsafe { ItemBuffer::from_bytes_unchecked(std::slice::from_raw_parts(data, size)) };
+    sender.send(buffer).is_ok()
+}
+
+extern "C" {
+    fn read_buffers(
+        file: &FileSpec,
+        context: *mut c_void,
+        callback: BufferCallback,
+        error_buffer: *mut c_char,
+    );
+}
@@ -0,0 +1,146 @@
+//! defines the [Relation] as well as its [members](RelationMember).
+
+use std::ffi::CStr;
+use std::marker::PhantomData;
+use std::os::raw::c_char;
+
+use crate::item::ItemType;
+#[cfg(feature = "serde")]
+use crate::object::OSMObject;
+use crate::object::ObjectId;
+
+/// A relation is a tagged list of [members](RelationMember) i.e. references to other objects.
+pub enum Relation {}
+
+impl Relation {
+    /// Get the relation's members
+    pub fn members(&self) -> &RelationMemberList {
+        unsafe { relation_members(self) }
+    }
+}
+
+/// A [Relation]'s list of members
+///
+/// Since its members are dynamically sized, they can only be accessed through an [iterator](RelationMemberIterator).
+pub enum RelationMemberList {}
+
+impl RelationMemberList {
+    /// Are there any members at all?
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Count the number of members.
+    ///
+    /// This requires iterating over the entire list.
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Return an iterator over all members.
+    pub fn iter(&self) -> RelationMemberIterator<'_> {
+        self.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a RelationMemberList {
+    type Item = &'a RelationMember;
+    type IntoIter = RelationMemberIterator<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        RelationMemberIterator {
+            current: unsafe { relation_member_list_begin(self) },
+            end: unsafe { relation_member_list_end(self) },
+            list_lifetime: PhantomData,
+        }
+    }
+}
+
+/// Iterator over [RelationMemberList]'s members
+#[derive(Clone)]
+pub struct RelationMemberIterator<'a> {
+    current: *const RelationMember,
+    end: *const RelationMember,
+    list_lifetime: PhantomData<&'a RelationMemberList>,
+}
+impl<'a> Iterator for RelationMemberIterator<'a> {
+    type Item = &'a RelationMember;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.current == self.end {
+            return None;
+        }
+
+        let member = unsafe { &*self.current };
+        unsafe { relation_member_iterator_increment(&mut self.current) };
+        Some(member)
+    }
+}
+
+/// A reference from a [Relation] to one of its members.
+///
+/// Besides the referenced object's type and id, each member stores the role it has in the relation.
+pub enum RelationMember {}
+
+impl RelationMember {
+    /// Get the referenced object's id
+    pub fn member_ref(&self) -> ObjectId {
+        unsafe { relation_member_ref(self) }
+    }
+
+    /// Get the referenced object's type
+    pub fn member_type(&self) -> ItemType {
+        unsafe { relation_member_type(self) }
+    }
+
+    /// Get the role the referenced object has in the relation
+    pub fn role(&self) -> &CStr {
+        unsafe { CStr::from_ptr(relation_member_role(self)) }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Relation {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state =
+            serializer.serialize_struct("Relation", OSMObject::SERIALIZED_FIELDS + 1)?;
+        self.serialize_fields(&mut state)?;
+        state.serialize_field("members", self.members())?;
+        state.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for RelationMemberList {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeSeq;
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        for member in self {
+            seq.serialize_element(member)?;
+        }
+        seq.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for RelationMember {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("RelationMember", 3)?;
+        state.serialize_field("type", &self.member_type())?;
+        state.serialize_field("ref", &self.member_ref())?;
+        state.serialize_field("role", &self.role().to_string_lossy())?;
+        state.end()
+    }
+}
+
+extern "C" {
+    fn relation_members(relation: &Relation) -> &RelationMemberList;
+    fn relation_member_list_begin(list: &RelationMemberList) -> *const RelationMember;
+    fn relation_member_list_end(list: &RelationMemberList) -> *const RelationMember;
+    fn relation_member_iterator_increment(iter: &mut *const RelationMember);
+    fn relation_member_ref(member: &RelationMember) -> ObjectId;
+    fn relation_member_type(member: &RelationMember) -> ItemType;
+    fn relation_member_role(member: &RelationMember) -> *const c_char;
+}
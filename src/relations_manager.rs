@@ -0,0 +1,294 @@
+//! defines the [RelationsManager] to assemble relations together with their members.
+//!
+//! This mirrors libosmium's `osmium::relations::RelationsManager`:
+//! In a first pass the manager selects interesting relations and members,
+//! which are collected in a second pass. As soon as all of a relation's members have been seen,
+//! the manager gets the complete relation.
+//!
+//! ```no_run
+//! use libosmium::relations_manager::{Relations, RelationsManager};
+//! use libosmium::{OSMObject, Relation, RelationMember};
+//!
+//! struct Routes;
+//! impl RelationsManager for Routes {
+//!     fn new_relation(&self, relation: &Relation) -> bool {
+//!         relation.tags().get("type") == Some("route")
+//!     }
+//!
+//!     fn new_member(&self, _relation: &Relation, member: &RelationMember, _n: usize) -> bool {
+//!         member.role().to_bytes() != b"stop"
+//!     }
+//!
+//!     fn complete_relation(&mut self, relation: &Relation, members: &[Option<&OSMObject>]) {
+//!         let collected = members.iter().flatten().count();
+//!         println!("Route {} has {} members besides stops", relation.id(), collected);
+//!     }
+//! }
+//!
+//! Relations::new(Routes).apply("planet.osm.pbf")?;
+//! # Ok::<(), libosmium::io::Error>(())
+//! ```
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::handler::Handler;
+use crate::io::{self, File, Reader};
+use crate::item::ItemType;
+use crate::item_stash::ItemStash;
+use crate::object::{OSMObject, ObjectId};
+use crate::relation::{Relation, RelationMember};
+
+/// A relations manager selects the relations and members to collect and processes the complete relations.
+///
+/// See the [module's documentation](self) for an example.
+pub trait RelationsManager {
+    /// Decide whether a relation should be collected.
+    ///
+    /// This is called in the first pass.
+    fn new_relation(&self, relation: &Relation) -> bool;
+
+    /// Decide whether a member of a collected relation should be collected.
+    ///
+    /// `n` is the member's position in the relation. By default, all members are collected.
+    /// This is called in the first pass.
+    fn new_member(&self, _relation: &Relation, _member: &RelationMember, _n: usize) -> bool {
+        true
+    }
+
+    /// Process a relation whose members have all been seen.
+    ///
+    /// `members` contains the member objects in the relation's order.
+    /// Members which haven't been selected by [`new_member`](RelationsManager::new_member) are `None`.
+    /// This is called in the second pass, or already in the first one if no member has been selected.
+    fn complete_relation(&mut self, relation: &Relation, members: &[Option<&OSMObject>]);
+
+    /// Process a relation of which some members are missing after the second pass.
+    ///
+    /// This is common for extracts, where relations reach beyond the extract's boundary.
+    /// Missing members are `None`, like the ones which haven't been selected. By default, nothing is done.
+    fn incomplete_relation(&mut self, _relation: &Relation, _members: &[Option<&OSMObject>]) {}
+
+    /// Finalize temporary or lazy data
+    ///
+    /// This is called at the end of the second pass, after all incomplete relations.
+    fn flush(&mut self) {}
+}
+
+/// Driver collecting relations and their members for a [RelationsManager] in two passes
+///
+/// The collected relations and member objects are copied into memory until the relations are complete.
+/// Each member object is only stored once, even if multiple relations reference it.
+#[derive(Debug)]
+pub struct Relations<M> {
+    manager: M,
+
+    /// The collected relations
+    relations: ItemStash,
+
+    /// State of each collected relation which hasn't been completed yet by the order they have been collected
+    states: HashMap<usize, RelationState>,
+
+    /// Number of relations collected so far
+    collected: usize,
+
+    /// The collected member objects
+    members: ItemStash,
+
+    /// Number of waiting relations using each member object by its handle in `members`
+    member_uses: Vec<usize>,
+
+    /// Members which haven't been seen yet and the relations' slots waiting for them
+    waiting: HashMap<(ItemType, ObjectId), Vec<(usize, usize)>>,
+}
+
+/// Collection state of a single relation
+#[derive(Debug)]
+struct RelationState {
+    /// The relation's handle in `Relations::relations`
+    relation: usize,
+
+    /// For every member its handle in `Relations::members`, once it has been seen
+    members: Vec<Option<usize>>,
+
+    /// Number of selected members which haven't been seen yet
+    missing: usize,
+}
+
+impl<M: RelationsManager> Relations<M> {
+    /// Create a driver for a relations manager.
+    pub fn new(manager: M) -> Self {
+        Self {
+            manager,
+            relations: ItemStash::new(),
+            states: HashMap::new(),
+            collected: 0,
+            members: ItemStash::new(),
+            member_uses: Vec::new(),
+            waiting: HashMap::new(),
+        }
+    }
+
+    /// Get the relations manager
+    pub fn manager(&self) -> &M {
+        &self.manager
+    }
+
+    /// Unwrap the relations manager
+    pub fn into_inner(self) -> M {
+        self.manager
+    }
+
+    /// Read a file twice, once for each pass.
+    ///
+    /// The first pass only reads the file's relations, like libosmium's `osmium::relations::read_relations`.
+    /// To use another source or to combine the passes with other handlers,
+    /// apply the handlers returned by [`first_pass`](Relations::first_pass)
+    /// and [`second_pass`](Relations::second_pass) yourself.
+    pub fn apply(&mut self, file: impl AsRef<Path>) -> Result<(), io::Error> {
+        let file = file.as_ref();
+        Reader::new(File::new(file)).apply_relations(&mut self.first_pass())?;
+        Reader::new(File::new(file)).apply(&mut self.second_pass())
+    }
+
+    /// Get the handler for the first pass which selects the relations and members.
+    pub fn first_pass(&mut self) -> FirstPass<'_, M> {
+        FirstPass(self)
+    }
+
+    /// Get the handler for the second pass which collects the members and completes the relations.
+    pub fn second_pass(&mut self) -> SecondPass<'_, M> {
+        SecondPass(self)
+    }
+
+    /// Is any collected relation still waiting for an object?
+    pub(crate) fn is_waiting_for(&self, item_type: ItemType, id: ObjectId) -> bool {
+        self.waiting.contains_key(&(item_type, id))
+    }
+
+    /// Get the relations manager mutably
+    pub(crate) fn manager_mut(&mut self) -> &mut M {
+        &mut self.manager
+    }
+
+    /// Store a relation selected by the manager and register its selected members
+    fn add_relation(&mut self, relation: &Relation) {
+        let index = self.collected;
+        self.collected += 1;
+        let mut state = RelationState {
+            relation: self.relations.add(relation),
+            members: Vec::new(),
+            missing: 0,
+        };
+        for (n, member) in relation.members().iter().enumerate() {
+            state.members.push(None);
+            if self.manager.new_member(relation, member, n) {
+                state.missing += 1;
+                self.waiting
+                    .entry((member.member_type(), member.member_ref()))
+                    .or_default()
+                    .push((index, n));
+            }
+        }
+
+        // Nothing to wait for
+        if state.missing == 0 {
+            self.complete(state, true);
+        } else {
+            self.states.insert(index, state);
+        }
+    }
+
+    /// Store an object, if any relation is waiting for it, and complete the relations
+    fn add_member(&mut self, object: &OSMObject) {
+        let key = (object.as_ref().item_type(), object.id());
+        let Some(slots) = self.waiting.remove(&key) else {
+            return;
+        };
+
+        let handle = self.members.add(object);
+        if self.member_uses.len() <= handle {
+            self.member_uses.resize(handle + 1, 0);
+        }
+        self.member_uses[handle] = slots.len();
+        for (index, n) in slots {
+            let state = self
+                .states
+                .get_mut(&index)
+                .expect("Waiting relations are pending");
+            state.members[n] = Some(handle);
+            state.missing -= 1;
+            if state.missing == 0 {
+                let state = self.states.remove(&index).unwrap();
+                self.complete(state, true);
+            }
+        }
+    }
+
+    /// Pass a relation and its members to the manager and free them
+    fn complete(&mut self, state: RelationState, complete: bool) {
+        let relation = self
+            .relations
+            .get(state.relation)
+            .downcast::<Relation>()
+            .expect("Only relations are stored");
+        let members: Vec<Option<&OSMObject>> = state
+            .members
+            .iter()
+            .map(|handle| self.members.get((*handle)?).downcast::<OSMObject>())
+            .collect();
+
+        if complete {
+            self.manager.complete_relation(relation, &members);
+        } else {
+            self.manager.incomplete_relation(relation, &members);
+        }
+
+        self.relations.remove(state.relation);
+        for handle in state.members.into_iter().flatten() {
+            self.member_uses[handle] -= 1;
+            if self.member_uses[handle] == 0 {
+                self.members.remove(handle);
+            }
+        }
+    }
+
+    /// Report the incomplete relations and free all collected data
+    fn finish(&mut self) {
+        let mut states: Vec<_> = self.states.drain().collect();
+        states.sort_unstable_by_key(|(index, _)| *index);
+        for (_, state) in states {
+            self.complete(state, false);
+        }
+        self.manager.flush();
+
+        self.relations.clear();
+        self.members.clear();
+        self.member_uses.clear();
+        self.waiting.clear();
+    }
+}
+
+/// Handler for the [first pass](Relations::first_pass)
+pub struct FirstPass<'r, M>(&'r mut Relations<M>);
+
+impl<M: RelationsManager> Handler for FirstPass<'_, M> {
+    fn relation(&mut self, relation: &Relation) {
+        if self.0.manager.new_relation(relation) {
+            self.0.add_relation(relation);
+        }
+    }
+}
+
+/// Handler for the [second pass](Relations::second_pass)
+pub struct SecondPass<'r, M>(&'r mut Relations<M>);
+
+impl<M: RelationsManager> Handler for SecondPass<'_, M> {
+    fn osm_object(&mut self, object: &OSMObject) {
+        self.0.add_member(object);
+    }
+
+    fn flush(&mut self) {
+        self.0.finish();
+    }
+}
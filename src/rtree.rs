@@ -0,0 +1,418 @@
+//! defines the [RTree], a spatial index over the items in an [ItemBuffer].
+//!
+//! The tree is bulk loaded once using the sort-tile-recursive algorithm and
+//! stores only the items' envelopes together with their offsets in the buffer.
+//!
+//! ```no_run
+//! use libosmium::handler::{from_fns, Handler};
+//! use libosmium::rtree::RTree;
+//! use libosmium::{BBox, ItemBuffer, Location};
+//!
+//! let mut shops = ItemBuffer::new();
+//! from_fns()
+//!     .node(|node| {
+//!         if node.tags().get("shop").is_some() {
+//!             shops.push(node);
+//!         }
+//!     })
+//!     .apply("city.osm.pbf")?;
+//!
+//! let tree = RTree::new(&shops);
+//! let bbox = BBox::from_degrees(13.37, 52.51, 13.38, 52.52);
+//! println!("{} shops in the box", tree.intersecting(&bbox).count());
+//! if let Some((offset, _shop)) = tree.nearest(Location::new(13.377, 52.516)).next() {
+//!     println!("The nearest shop is at byte {offset}");
+//! }
+//! # Ok::<(), libosmium::io::Error>(())
+//! ```
+
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::ops::Range;
+
+use crate::area::Area;
+use crate::buffer::ItemBuffer;
+use crate::item::Item;
+use crate::location::{BBox, Location};
+use crate::node::Node;
+use crate::way::Way;
+
+/// Maximum number of children per tree node
+const NODE_CAPACITY: usize = 16;
+
+/// Spatial index over the nodes, ways and areas in an [ItemBuffer]
+///
+/// Nodes are indexed by their location, ways and areas by their envelopes.
+/// Items without a valid location (for example ways whose nodes' locations haven't been set) and relations are skipped.
+///
+/// Queries return the items together with their offset in bytes in the buffer.
+/// The offsets are the same as the ones reported by the buffer's [iterator](ItemBuffer::iter)
+/// and stay valid as long as the buffer isn't modified.
+#[derive(Clone, Debug)]
+pub struct RTree<'b> {
+    buffer: &'b ItemBuffer,
+    index: Index,
+}
+
+/// The tree itself, which only knows the items' offsets and envelopes
+#[derive(Clone, Debug)]
+struct Index {
+    /// The indexed items' offsets and envelopes, ordered by the leaves they are in
+    entries: Vec<(usize, BBox)>,
+
+    /// The tree's levels starting at the leaves
+    ///
+    /// A node's children are the range of entries (for leaves) or of nodes in the level below.
+    /// The last level contains only the root, unless the tree is empty.
+    levels: Vec<Vec<TreeNode>>,
+}
+
+/// Node in an [RTree]
+#[derive(Clone, Debug)]
+struct TreeNode {
+    envelope: BBox,
+    children: Range<usize>,
+}
+
+impl<'b> RTree<'b> {
+    /// Bulk load a tree for the items in a buffer.
+    pub fn new(buffer: &'b ItemBuffer) -> Self {
+        let mut entries = Vec::new();
+        let mut items = buffer.iter();
+        loop {
+            let offset = items.offset();
+            let Some(item) = items.next() else {
+                break;
+            };
+            let envelope = envelope(item);
+            if envelope.is_defined() {
+                entries.push((offset, envelope));
+            }
+        }
+        Self {
+            buffer,
+            index: Index::new(entries),
+        }
+    }
+
+    /// Get the indexed buffer
+    pub fn buffer(&self) -> &'b ItemBuffer {
+        self.buffer
+    }
+
+    /// Get the number of indexed items
+    pub fn len(&self) -> usize {
+        self.index.entries.len()
+    }
+
+    /// Is the tree empty?
+    pub fn is_empty(&self) -> bool {
+        self.index.entries.is_empty()
+    }
+
+    /// Return an iterator over the items whose envelopes share at least one point with a box.
+    pub fn intersecting(&self, bbox: &BBox) -> impl Iterator<Item = (usize, &'b Item)> + '_ {
+        self.index
+            .intersecting(bbox)
+            .map(|offset| (offset, self.get(offset)))
+    }
+
+    /// Return an iterator over all items ordered by the distance of their envelopes to a location.
+    ///
+    /// The distance is measured on the plane of longitude and latitude,
+    /// so it is only a good approximation of the real distance for short distances away from the poles.
+    /// Ways and areas are as close as their envelopes, which can be closer than their actual geometry.
+    pub fn nearest(&self, location: Location) -> Nearest<'_, 'b> {
+        Nearest {
+            tree: self,
+            offsets: self.index.nearest(location),
+        }
+    }
+
+    /// Get the item at an offset
+    fn get(&self, offset: usize) -> &'b Item {
+        unsafe { self.buffer.get_unchecked(offset) }
+    }
+}
+
+impl Index {
+    /// Bulk load a tree for entries of offsets and envelopes
+    fn new(mut entries: Vec<(usize, BBox)>) -> Self {
+        let mut levels = Vec::new();
+        let mut nodes = pack(&mut entries, |(_, envelope)| *envelope);
+        while nodes.len() > 1 {
+            let parents = pack(&mut nodes, |node| node.envelope);
+            levels.push(nodes);
+            nodes = parents;
+        }
+        if !nodes.is_empty() {
+            levels.push(nodes);
+        }
+        Self { entries, levels }
+    }
+
+    /// Return an iterator over the offsets of the entries intersecting a box
+    fn intersecting(&self, bbox: &BBox) -> impl Iterator<Item = usize> + '_ {
+        let bbox = *bbox;
+        let mut stack = match self.levels.last() {
+            Some(root) => vec![(self.levels.len() - 1, 0..root.len())],
+            None => Vec::new(),
+        };
+        let mut leaf = 0..0;
+        std::iter::from_fn(move || loop {
+            if let Some(index) = leaf.next() {
+                let (offset, envelope) = self.entries[index];
+                if envelope.intersects(&bbox) {
+                    return Some(offset);
+                }
+                continue;
+            }
+
+            let (level, nodes) = stack.last_mut()?;
+            let level = *level;
+            let Some(index) = nodes.next() else {
+                stack.pop();
+                continue;
+            };
+            let node = &self.levels[level][index];
+            if node.envelope.intersects(&bbox) {
+                if level == 0 {
+                    leaf = node.children.clone();
+                } else {
+                    stack.push((level - 1, node.children.clone()));
+                }
+            }
+        })
+    }
+
+    /// Return an iterator over the offsets of all entries ordered by their distance to a location
+    fn nearest(&self, location: Location) -> NearestOffsets<'_> {
+        let mut queue = BinaryHeap::new();
+        if let Some(root) = self.levels.last() {
+            let level = self.levels.len() - 1;
+            for (index, node) in root.iter().enumerate() {
+                queue.push(Reverse((
+                    distance(&node.envelope, location),
+                    Candidate::Node(level, index),
+                )));
+            }
+        }
+        NearestOffsets {
+            index: self,
+            location,
+            queue,
+        }
+    }
+}
+
+/// Iterator over an [RTree]'s items ordered by their distance to a location
+///
+/// This struct is created by the [`nearest`](RTree::nearest) method.
+#[must_use = "iterators are lazy and do nothing unless consumed"]
+pub struct Nearest<'t, 'b> {
+    tree: &'t RTree<'b>,
+    offsets: NearestOffsets<'t>,
+}
+
+impl<'b> Iterator for Nearest<'_, 'b> {
+    type Item = (usize, &'b Item);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let offset = self.offsets.next()?;
+        Some((offset, self.tree.get(offset)))
+    }
+}
+
+/// Iterator over an [Index]'s offsets ordered by their distance to a location
+struct NearestOffsets<'t> {
+    index: &'t Index,
+    location: Location,
+    queue: BinaryHeap<Reverse<(u128, Candidate)>>,
+}
+
+/// Tree node or entry waiting in the [NearestOffsets] iterator's queue
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Candidate {
+    /// An entry's index
+    Entry(usize),
+
+    /// A node's level and index
+    Node(usize, usize),
+}
+
+impl Iterator for NearestOffsets<'_> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(Reverse((_, candidate))) = self.queue.pop() {
+            match candidate {
+                Candidate::Entry(index) => return Some(self.index.entries[index].0),
+                Candidate::Node(level, index) => {
+                    let node = &self.index.levels[level][index];
+                    for child in node.children.clone() {
+                        let (envelope, candidate) = if level == 0 {
+                            (self.index.entries[child].1, Candidate::Entry(child))
+                        } else {
+                            let node = &self.index.levels[level - 1][child];
+                            (node.envelope, Candidate::Node(level - 1, child))
+                        };
+                        self.queue
+                            .push(Reverse((distance(&envelope, self.location), candidate)));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Get the envelope of a node, way or area
+fn envelope(item: &Item) -> BBox {
+    let mut envelope = BBox::new();
+    if let Some(node) = item.downcast::<Node>() {
+        if node.location().is_valid() {
+            envelope.extend(node.location());
+        }
+    } else if let Some(way) = item.downcast::<Way>() {
+        envelope = way.nodes().envelope();
+    } else if let Some(area) = item.downcast::<Area>() {
+        envelope = area.envelope();
+    }
+    envelope
+}
+
+/// Sort children into tiles using the sort-tile-recursive algorithm and create a parent node for each tile
+fn pack<T>(children: &mut [T], envelope: impl Fn(&T) -> BBox) -> Vec<TreeNode> {
+    let center = |child: &T| {
+        let envelope = envelope(child);
+        (
+            envelope.bottom_left.raw_x as i64 + envelope.top_right.raw_x as i64,
+            envelope.bottom_left.raw_y as i64 + envelope.top_right.raw_y as i64,
+        )
+    };
+
+    // Split into vertical slices of about sqrt(parents) tiles each and sort those by latitude
+    let parents = children.len().div_ceil(NODE_CAPACITY);
+    let slice_size = NODE_CAPACITY * (parents as f64).sqrt().ceil() as usize;
+    children.sort_by_key(|child| center(child).0);
+    for slice in children.chunks_mut(slice_size.max(1)) {
+        slice.sort_by_key(|child| center(child).1);
+    }
+
+    let mut nodes = Vec::with_capacity(parents);
+    for (index, tile) in children.chunks(NODE_CAPACITY).enumerate() {
+        let mut node_envelope = BBox::new();
+        for child in tile {
+            let child = envelope(child);
+            node_envelope
+                .extend(child.bottom_left)
+                .extend(child.top_right);
+        }
+        let start = index * NODE_CAPACITY;
+        nodes.push(TreeNode {
+            envelope: node_envelope,
+            children: start..start + tile.len(),
+        });
+    }
+    nodes
+}
+
+/// Squared distance between a box and a location in `1/PRECISION` degrees
+fn distance(bbox: &BBox, location: Location) -> u128 {
+    let axis = |min: i32, max: i32, value: i32| {
+        if value < min {
+            (min as i64 - value as i64).unsigned_abs()
+        } else if value > max {
+            (value as i64 - max as i64).unsigned_abs()
+        } else {
+            0
+        }
+    };
+    let dx = axis(bbox.bottom_left.raw_x, bbox.top_right.raw_x, location.raw_x) as u128;
+    let dy = axis(bbox.bottom_left.raw_y, bbox.top_right.raw_y, location.raw_y) as u128;
+    dx * dx + dy * dy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pseudo random boxes of up to a tenth degree between -10 and 10 degrees, offset by their index
+    fn entries(count: usize) -> Vec<(usize, BBox)> {
+        let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut random = move |range: f64| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            (state >> 11) as f64 / (1u64 << 53) as f64 * range
+        };
+        (0..count)
+            .map(|offset| {
+                let (lon, lat) = (random(20.0) - 10.0, random(20.0) - 10.0);
+                let (width, height) = (random(0.1), random(0.1));
+                (
+                    offset,
+                    BBox::from_degrees(lon, lat, lon + width, lat + height),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nearest_against_brute_force() {
+        for count in [0, 1, 15, 16, 17, 300, 5000] {
+            let entries = entries(count);
+            let index = Index::new(entries.clone());
+            for location in [
+                Location::new(0.0, 0.0),
+                Location::new(-9.5, 3.3),
+                Location::new(50.0, -50.0),
+            ] {
+                let found: Vec<usize> = index.nearest(location).collect();
+                let distances: Vec<u128> = found
+                    .iter()
+                    .map(|offset| distance(&entries[*offset].1, location))
+                    .collect();
+                let mut expected: Vec<u128> = entries
+                    .iter()
+                    .map(|(_, envelope)| distance(envelope, location))
+                    .collect();
+                expected.sort_unstable();
+                assert_eq!(distances, expected);
+
+                let mut found = found;
+                found.sort_unstable();
+                assert_eq!(found, (0..count).collect::<Vec<_>>());
+            }
+        }
+    }
+
+    #[test]
+    fn intersecting_against_brute_force() {
+        let entries = entries(5000);
+        let index = Index::new(entries.clone());
+        for bbox in [
+            BBox::from_degrees(-1.0, -1.0, 1.0, 1.0),
+            BBox::from_degrees(-10.0, 5.0, -9.0, 10.0),
+            BBox::from_degrees(20.0, 20.0, 30.0, 30.0),
+        ] {
+            let mut found: Vec<usize> = index.intersecting(&bbox).collect();
+            found.sort_unstable();
+            let expected: Vec<usize> = entries
+                .iter()
+                .filter(|(_, envelope)| envelope.intersects(&bbox))
+                .map(|(offset, _)| *offset)
+                .collect();
+            assert_eq!(found, expected);
+        }
+    }
+
+    #[test]
+    fn distance_to_box() {
+        let bbox = BBox::from_degrees(0.0, 0.0, 1.0, 1.0);
+        assert_eq!(distance(&bbox, Location::new(0.5, 0.5)), 0);
+        assert_eq!(distance(&bbox, Location::new(1.0, 1.0)), 0);
+        assert_eq!(distance(&bbox, Location::new(0.5, -0.0000003)), 9);
+        assert_eq!(distance(&bbox, Location::new(1.0000003, 1.0000004)), 25);
+    }
+}
@@ -125,7 +125,6 @@ extern "C" {
 /// The actual data is stored in the same way as in [`TagList`]
 /// and therefore uses the same accessing logic. But it manages its own memory using a [`Vec<u8>`]
 #[derive(Clone)]
-#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct OwnedTagList(pub Vec<u8>);
 
 impl OwnedTagList {
@@ -192,6 +191,67 @@ impl std::fmt::Debug for OwnedTagList {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for TagList {
+    /// Serialize the tags as map from keys to values
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(self, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for OwnedTagList {
+    /// Serialize the tags as map from keys to values
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize(self, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for OwnedTagList {
+    /// Deserialize the tags from a map from keys to values
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Visitor;
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = OwnedTagList;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a map of strings without NUL characters")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(
+                self,
+                mut map: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut tags = OwnedTagList::new();
+                while let Some((key, value)) = map.next_entry::<String, String>()? {
+                    if key.contains('\0') || value.contains('\0') {
+                        return Err(serde::de::Error::custom(
+                            "tags can't contain NUL characters",
+                        ));
+                    }
+                    tags.push_pair(&key, &value);
+                }
+                Ok(tags)
+            }
+        }
+        deserializer.deserialize_map(Visitor)
+    }
+}
+
+#[cfg(feature = "serde")]
+fn serialize<'t, S: serde::Serializer>(
+    tags: impl IntoIterator<Item = (&'t str, &'t str)> + Copy,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    use serde::ser::SerializeMap;
+    let mut map = serializer.serialize_map(Some(tags.into_iter().count()))?;
+    for (key, value) in tags {
+        map.serialize_entry(key, value)?;
+    }
+    map.end()
+}
+
 fn debug<'t>(
     tags: impl IntoIterator<Item = (&'t str, &'t str)>,
     f: &mut std::fmt::Formatter<'_>,
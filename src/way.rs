@@ -1,4 +1,6 @@
 use crate::node_ref_list::WayNodeList;
+#[cfg(feature = "serde")]
+use crate::object::OSMObject;
 
 /// A way is a tagged list of [Nodes](crate::Node).
 pub enum Way {}
@@ -15,6 +17,17 @@ impl Way {
     }
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for Way {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("Way", OSMObject::SERIALIZED_FIELDS + 1)?;
+        self.serialize_fields(&mut state)?;
+        state.serialize_field("nodes", self.nodes())?;
+        state.end()
+    }
+}
+
 extern "C" {
     fn way_nodes_const(way: &Way) -> &WayNodeList;
     fn way_nodes(way: &mut Way) -> &mut WayNodeList;